@@ -2,7 +2,7 @@ pub mod plugins;
 
 use bevy::{prelude::*, render::{settings::{Backends, RenderCreation, WgpuSettings}, RenderPlugin}};
 use bevy_rapier3d::prelude::*;
-use plugins::{camera_controller::CameraControllerPlugin, hover::HoverPlugin, light::LightPlugin, tile::{TilePlugin, TileSettings, GRID_SIZE}, tools::ToolsPlugin, water::WaterPlugin};
+use plugins::{breeding::BreedingPlugin, camera_controller::CameraControllerPlugin, camera_input::CameraInputPlugin, decoration::DecorationPlugin, disease::DiseasePlugin, economy::EconomyPlugin, equipment::EquipmentPlugin, first_person::FirstPersonPlugin, follow_camera::FollowCameraPlugin, food::FoodPlugin, gardener::GardenerPlugin, hover::HoverPlugin, inspector::InspectorPlugin, koi::KoiPlugin, light::LightPlugin, market::MarketPlugin, night_light::NightLightPlugin, notification::NotificationPlugin, pathfinding::PathfindingPlugin, pedigree::PedigreePlugin, plant::PlantPlugin, pond::PondPlugin, predator::PredatorPlugin, save::SavePlugin, season::SeasonPlugin, show::ShowPlugin, tile::{TilePlugin, TileSettings, GRID_SIZE}, tools::ToolsPlugin, visitor::VisitorPlugin, water::WaterPlugin, weather::WeatherPlugin};
 
 #[derive(Component)]
 struct Ground;
@@ -45,7 +45,9 @@ fn main() {
         )
         .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
         // .add_plugins(RapierDebugRenderPlugin::default())
-        .add_plugins((WaterPlugin, CameraControllerPlugin, LightPlugin, HoverPlugin, TilePlugin, ToolsPlugin))
+        .add_plugins((WaterPlugin, CameraControllerPlugin, LightPlugin, HoverPlugin, TilePlugin, ToolsPlugin, NotificationPlugin, InspectorPlugin, ShowPlugin, EconomyPlugin, PathfindingPlugin, DecorationPlugin, SavePlugin, FirstPersonPlugin, SeasonPlugin))
+        .add_plugins((FoodPlugin, KoiPlugin, PondPlugin, EquipmentPlugin, PlantPlugin, PedigreePlugin, BreedingPlugin, DiseasePlugin, PredatorPlugin, MarketPlugin, VisitorPlugin, GardenerPlugin, FollowCameraPlugin, CameraInputPlugin, NightLightPlugin))
+        .add_plugins(WeatherPlugin)
         .add_systems(Startup, setup)
         .run();
 }
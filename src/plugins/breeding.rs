@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+use super::{koi::{spawn_koi, Koi, KoiAssets}, notification::Notification, pedigree::Pedigree, pond::Ponds, season::CurrentSeason, tile::{to_top_down, TileSettings}};
+
+// real seconds between breeding attempts
+const BREEDING_TICK: f32 = 30.0;
+// chance each pond with a healthy pair produces a hatchling per tick
+const BREEDING_CHANCE: f64 = 0.2;
+const MIN_BREEDING_HEALTH: f32 = 0.7;
+const MAX_BREEDING_HUNGER: f32 = 0.5;
+
+#[derive(Resource)]
+struct BreedingSeason {
+    timer: Timer,
+}
+
+pub struct BreedingPlugin;
+
+impl Plugin for BreedingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(BreedingSeason {
+                timer: Timer::from_seconds(BREEDING_TICK, TimerMode::Repeating),
+            })
+            .add_systems(Update, breed);
+    }
+}
+
+fn breed(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut notifications: EventWriter<Notification>,
+    time: Res<Time>,
+    mut breeding_season: ResMut<BreedingSeason>,
+    season: Res<CurrentSeason>,
+    mut pedigree: ResMut<Pedigree>,
+    koi_assets: Res<KoiAssets>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+    koi_query: Query<(&Koi, &Transform)>,
+) {
+    if !breeding_season.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut pond_koi: HashMap<u32, Vec<(&Koi, &Transform)>> = HashMap::new();
+
+    for (koi, transform) in koi_query.iter() {
+        if let Some(pond) = ponds.pond_at(tile_settings.grid_coords(to_top_down(transform.translation))) {
+            pond_koi.entry(pond.id).or_default().push((koi, transform));
+        }
+    }
+
+    for (pond_id, koi) in pond_koi {
+        let crowded = ponds.get(pond_id).map_or(true, |pond| koi.len() >= pond.fish_capacity());
+
+        if crowded || !rng.gen_bool((BREEDING_CHANCE * season.0.breeding()).min(1.0)) {
+            continue;
+        }
+
+        let breeders: Vec<_> = koi
+            .into_iter()
+            .filter(|(koi, _)| koi.is_adult() && koi.health >= MIN_BREEDING_HEALTH && koi.hunger <= MAX_BREEDING_HUNGER)
+            .collect();
+
+        let parents: Vec<_> = breeders.choose_multiple(&mut rng, 2).collect();
+
+        let [(first_parent, transform), (second_parent, _)] = parents[..] else {
+            continue;
+        };
+
+        let mut hatchling = Koi::hatchling(first_parent, second_parent, &mut rng);
+        pedigree.register(&mut hatchling, Some([first_parent.id, second_parent.id]));
+
+        notifications.send(Notification(format!(
+            "{} and {} had a hatchling, {} the {}",
+            first_parent.name,
+            second_parent.name,
+            hatchling.name,
+            hatchling.variety(),
+        )));
+
+        spawn_koi(&mut commands, &mut materials, &koi_assets, hatchling, to_top_down(transform.translation));
+    }
+}
@@ -1,5 +1,187 @@
-use bevy::prelude::*;
-use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
+use std::{f32::consts::{FRAC_PI_2, FRAC_PI_4, TAU}, fmt};
+
+use bevy::{prelude::*, render::camera::ScalingMode};
+use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin, PanOrbitCameraSystemSet};
+
+use super::{notification::Notification, pathfinding::Pathfinder, pond::Ponds, save::{BookmarkRecord, GardenLoaded}, tile::{to_top_down, TileSettings}, water::WATER_LEVEL};
+
+// closest the camera may come to the ground or the water surface
+const GROUND_CLEARANCE: f32 = 1.0;
+// keeps the camera above the horizon and stops it flipping over the top
+const MIN_PITCH: f32 = 0.05;
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+// the orthographic camera draws everything this far behind it too, so tiles and decorations are
+// never cut off when zoomed in close and the camera sits low
+const PLANNING_DEPTH: f32 = 500.0;
+
+/// Keys for the numbered bookmark slots, held with Ctrl to store and pressed alone to recall.
+const SLOT_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CameraMode {
+    Orbit,
+    /// Orthographic top down view lined up with the grid, for laying out ponds and paths.
+    Planning,
+    /// Walking around the garden at eye height, the orbit camera is switched off.
+    FirstPerson,
+    /// Orbiting a koi as it swims, or touring from one koi to the next.
+    FollowKoi,
+}
+
+/// Where the orbit camera looks from, everything needed to ease back to the same view later.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraView {
+    pub focus: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub radius: f32,
+}
+
+impl CameraView {
+    fn of(camera: &PanOrbitCamera) -> Self {
+        Self {
+            focus: camera.target_focus,
+            yaw: camera.target_yaw,
+            pitch: camera.target_pitch,
+            radius: camera.target_radius,
+        }
+    }
+
+    /// Sets the camera off towards this view, the orbit camera's smoothing does the easing.
+    fn apply(&self, camera: &mut PanOrbitCamera) {
+        // turn whichever way round is shorter instead of unwinding every orbit made since
+        let turns = ((camera.target_yaw - self.yaw) / TAU).round();
+
+        camera.target_focus = self.focus;
+        camera.target_yaw = self.yaw + turns * TAU;
+        camera.target_pitch = self.pitch;
+        camera.target_radius = self.radius;
+    }
+}
+
+/// How far the camera may wander, worked out from the size of the tile grid.
+#[derive(Resource, Debug, Default)]
+pub struct CameraBounds {
+    pub area: Rect,
+    pub min_radius: f32,
+    pub max_radius: f32,
+}
+
+impl CameraBounds {
+    fn from_tiles(tile_settings: &TileSettings) -> Self {
+        let area = tile_settings.bounds();
+
+        Self {
+            area,
+            min_radius: tile_settings.tile_size,
+            max_radius: area.size().max_element(),
+        }
+    }
+
+    /// Pulls `view` back inside the garden, keeping the camera at least `GROUND_CLEARANCE`
+    /// above the `ground` height under it.
+    fn constrain(&self, view: CameraView, ground: impl Fn(Vec2) -> f32) -> CameraView {
+        let focus = to_top_down(view.focus).clamp(self.area.min, self.area.max);
+        let focus = Vec3::new(focus.x, view.focus.y, focus.y);
+        let radius = view.radius.clamp(self.min_radius, self.max_radius);
+        let mut pitch = view.pitch.clamp(MIN_PITCH, MAX_PITCH);
+
+        // same placement as the orbit camera, yaw turns around the focus and pitch lifts above it
+        let eye = to_top_down(focus) + Vec2::new(view.yaw.sin(), view.yaw.cos()) * radius * pitch.cos();
+        let lift = ground(eye) + GROUND_CLEARANCE - focus.y;
+
+        if radius * pitch.sin() < lift {
+            pitch = (lift / radius).min(1.0).asin().clamp(MIN_PITCH, MAX_PITCH);
+        }
+
+        CameraView { focus, yaw: view.yaw, pitch, radius }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum CameraPreset {
+    TopDown,
+    Isometric,
+    PondLevel,
+}
+
+impl CameraPreset {
+    const ALL: [CameraPreset; 3] = [CameraPreset::TopDown, CameraPreset::Isometric, CameraPreset::PondLevel];
+
+    /// The view for this preset, the overviews look at the whole garden and pond level at the pond
+    /// nearest to `focus`.
+    fn view(&self, focus: Vec3, ponds: &Ponds, tile_settings: &TileSettings) -> CameraView {
+        // the overview presets take in the whole grid from as far out as the camera may go
+        let garden = tile_settings.bounds();
+        let garden_center = Vec3::new(garden.center().x, 0.0, garden.center().y);
+        let garden_size = garden.size().max_element();
+
+        match self {
+            // just short of straight down so yaw stays well defined
+            CameraPreset::TopDown => CameraView {
+                focus: garden_center,
+                yaw: 0.0,
+                pitch: FRAC_PI_2 - 0.01,
+                radius: garden_size,
+            },
+            CameraPreset::Isometric => CameraView {
+                focus: garden_center,
+                yaw: FRAC_PI_4,
+                pitch: 35.264_f32.to_radians(),
+                radius: garden_size,
+            },
+            CameraPreset::PondLevel => {
+                let centers = ponds.iter().map(|pond| {
+                    let sum: Vec2 = pond.tiles.iter().map(|coords| tile_settings.world_position(*coords)).sum();
+                    sum / pond.tiles.len().max(1) as f32
+                });
+
+                let center = centers
+                    .min_by(|a, b| a.distance(to_top_down(focus)).total_cmp(&b.distance(to_top_down(focus))))
+                    .unwrap_or(to_top_down(focus));
+
+                CameraView {
+                    focus: Vec3::new(center.x, WATER_LEVEL, center.y),
+                    yaw: 0.0,
+                    pitch: 0.12,
+                    radius: tile_settings.tile_size * 3.0,
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for CameraPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CameraPreset::TopDown => write!(f, "top down"),
+            CameraPreset::Isometric => write!(f, "isometric"),
+            CameraPreset::PondLevel => write!(f, "pond level"),
+        }
+    }
+}
+
+/// Camera views stored in the numbered slots, and the preset 0 last switched to.
+#[derive(Resource, Debug, Default)]
+pub struct CameraBookmarks {
+    pub slots: [Option<CameraView>; 9],
+    preset: Option<usize>,
+}
+
+/// The perspective view to go back to when leaving planning mode.
+#[derive(Resource, Debug, Default)]
+struct OrbitReturn(Option<CameraView>);
 
 pub struct CameraControllerPlugin;
 
@@ -7,7 +189,16 @@ impl Plugin for CameraControllerPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_plugins(PanOrbitCameraPlugin)
-            .add_systems(Startup, setup);
+            .insert_resource(CameraBookmarks::default())
+            .insert_resource(CameraBounds::default())
+            .insert_resource(OrbitReturn::default())
+            .insert_state(CameraMode::Orbit)
+            .add_systems(Startup, setup)
+            .add_systems(OnEnter(CameraMode::Planning), enter_planning)
+            .add_systems(OnExit(CameraMode::Planning), exit_planning)
+            .add_systems(Update, (load_bookmarks, toggle_planning))
+            .add_systems(Update, (handle_bookmarks, cycle_presets).run_if(not(in_state(CameraMode::FirstPerson))))
+            .add_systems(Update, (update_bounds, constrain_camera).chain().before(PanOrbitCameraSystemSet));
     }
 }
 
@@ -20,3 +211,164 @@ fn setup(mut commands: Commands) {
         PanOrbitCamera::default()
     ));
 }
+
+fn update_bounds(tile_settings: Res<TileSettings>, mut bounds: ResMut<CameraBounds>) {
+    if tile_settings.is_changed() {
+        *bounds = CameraBounds::from_tiles(&tile_settings);
+    }
+}
+
+fn constrain_camera(
+    bounds: Res<CameraBounds>,
+    pathfinder: Res<Pathfinder>,
+    tile_settings: Res<TileSettings>,
+    mut camera_query: Query<&mut PanOrbitCamera>,
+) {
+    // off the grid there is only the ground plane, which sits below the water anyway
+    let ground = |position: Vec2| {
+        pathfinder
+            .height(tile_settings.grid_coords(position))
+            .unwrap_or_default()
+            .max(WATER_LEVEL)
+    };
+
+    // the orbit camera works out its own targets from its transform on the first frame
+    for mut camera in camera_query.iter_mut().filter(|camera| camera.initialized) {
+        let view = CameraView::of(&camera);
+        let constrained = bounds.constrain(view, ground);
+
+        if constrained != view {
+            constrained.apply(&mut camera);
+        }
+    }
+}
+
+fn toggle_planning(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    state: Res<State<CameraMode>>,
+    mut next_state: ResMut<NextState<CameraMode>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Tab) {
+        return;
+    }
+
+    match state.get() {
+        CameraMode::Planning => next_state.set(CameraMode::Orbit),
+        _ => next_state.set(CameraMode::Planning),
+    }
+}
+
+fn enter_planning(
+    mut orbit_return: ResMut<OrbitReturn>,
+    mut camera_query: Query<(&mut PanOrbitCamera, &mut Projection)>,
+) {
+    let (mut camera, mut projection) = camera_query.single_mut();
+    let view = CameraView::of(&camera);
+
+    orbit_return.0 = Some(view);
+
+    // the orbit camera zooms an orthographic projection by scaling it with the radius, so a fixed
+    // vertical size of one keeps about as much of the garden in view as the perspective camera
+    *projection = Projection::Orthographic(OrthographicProjection {
+        near: -PLANNING_DEPTH,
+        scaling_mode: ScalingMode::FixedVertical(1.0),
+        ..default()
+    });
+
+    CameraView { yaw: 0.0, pitch: MAX_PITCH, ..view }.apply(&mut camera);
+
+    // lock the view to the grid, panning and zooming still work
+    camera.yaw_lower_limit = Some(camera.target_yaw);
+    camera.yaw_upper_limit = Some(camera.target_yaw);
+    camera.pitch_lower_limit = Some(MAX_PITCH);
+    camera.pitch_upper_limit = Some(MAX_PITCH);
+    camera.force_update = true;
+}
+
+fn exit_planning(
+    mut orbit_return: ResMut<OrbitReturn>,
+    mut camera_query: Query<(&mut PanOrbitCamera, &mut Projection)>,
+) {
+    let (mut camera, mut projection) = camera_query.single_mut();
+
+    *projection = Projection::Perspective(PerspectiveProjection::default());
+
+    camera.yaw_lower_limit = None;
+    camera.yaw_upper_limit = None;
+    camera.pitch_lower_limit = None;
+    camera.pitch_upper_limit = None;
+
+    // come back to the old angle, but over wherever the plan was panned to
+    if let Some(view) = orbit_return.0.take() {
+        CameraView { focus: camera.target_focus, ..view }.apply(&mut camera);
+    }
+
+    camera.force_update = true;
+}
+
+fn handle_bookmarks(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut notifications: EventWriter<Notification>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mut camera_query: Query<&mut PanOrbitCamera>,
+) {
+    let Some(slot) = SLOT_KEYS.iter().position(|key| keyboard_input.just_pressed(*key)) else {
+        return;
+    };
+
+    let mut camera = camera_query.single_mut();
+
+    if keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        bookmarks.slots[slot] = Some(CameraView::of(&camera));
+        notifications.send(Notification(format!("Saved the view to bookmark {}", slot + 1)));
+        return;
+    }
+
+    match bookmarks.slots[slot] {
+        Some(view) => view.apply(&mut camera),
+        None => {
+            notifications.send(Notification(format!("Bookmark {} is empty, store a view with Ctrl+{}", slot + 1, slot + 1)));
+        }
+    }
+}
+
+fn cycle_presets(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut notifications: EventWriter<Notification>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+    mut camera_query: Query<&mut PanOrbitCamera>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Digit0) {
+        return;
+    }
+
+    let index = bookmarks.preset.map_or(0, |index| (index + 1) % CameraPreset::ALL.len());
+    let preset = CameraPreset::ALL[index];
+    let mut camera = camera_query.single_mut();
+
+    preset.view(camera.target_focus, &ponds, &tile_settings).apply(&mut camera);
+    bookmarks.preset = Some(index);
+
+    notifications.send(Notification(format!("Camera: {} view", preset)));
+}
+
+fn load_bookmarks(mut garden_loaded: EventReader<GardenLoaded>, mut bookmarks: ResMut<CameraBookmarks>) {
+    let Some(GardenLoaded(save)) = garden_loaded.read().last() else {
+        return;
+    };
+
+    bookmarks.slots = Default::default();
+
+    for record in save.camera_bookmarks.iter() {
+        if let Some(slot) = bookmarks.slots.get_mut(record.slot as usize) {
+            *slot = Some(CameraView {
+                focus: Vec3::from_array(record.focus),
+                yaw: record.yaw,
+                pitch: record.pitch,
+                radius: record.radius,
+            });
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::{fmt, fs, io::ErrorKind};
+
+use bevy::{input::InputSystem, prelude::*};
+use bevy_panorbit_camera::PanOrbitCamera;
+use serde::{Deserialize, Serialize};
+
+use super::notification::Notification;
+
+const SETTINGS_PATH: &str = "camera_input.json";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum CameraButton {
+    Left,
+    Right,
+    Middle,
+}
+
+impl From<CameraButton> for MouseButton {
+    fn from(button: CameraButton) -> Self {
+        match button {
+            CameraButton::Left => MouseButton::Left,
+            CameraButton::Right => MouseButton::Right,
+            CameraButton::Middle => MouseButton::Middle,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum CameraModifier {
+    Shift,
+    Control,
+    Alt,
+}
+
+impl CameraModifier {
+    /// The orbit camera only takes a single modifier key, so only the left hand ones count.
+    fn key(&self) -> KeyCode {
+        match self {
+            CameraModifier::Shift => KeyCode::ShiftLeft,
+            CameraModifier::Control => KeyCode::ControlLeft,
+            CameraModifier::Alt => KeyCode::AltLeft,
+        }
+    }
+}
+
+/// A mouse button together with the key that has to be held down with it, if any.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct Gesture {
+    pub button: CameraButton,
+    pub modifier: Option<CameraModifier>,
+}
+
+impl Gesture {
+    fn is_held(&self, keyboard_input: &ButtonInput<KeyCode>) -> bool {
+        self.modifier.map_or(true, |modifier| keyboard_input.pressed(modifier.key()))
+    }
+
+    /// Whether a click of this gesture could also be read as a click of `other`.
+    fn overlaps(&self, other: &Gesture) -> bool {
+        self.button == other.button && (self.modifier.is_none() || other.modifier.is_none() || self.modifier == other.modifier)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum CameraInputError {
+    Unreadable(String),
+    /// The gesture would fire on every plain left click, which belongs to the tools.
+    ClashesWithTools(&'static str),
+    OrbitAndPanOverlap,
+}
+
+impl fmt::Display for CameraInputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CameraInputError::Unreadable(error) => write!(f, "Could not read {}: {}", SETTINGS_PATH, error),
+            CameraInputError::ClashesWithTools(gesture) => {
+                write!(f, "Camera {} on the left mouse button needs a modifier key, plain left clicks are for the tools", gesture)
+            }
+            CameraInputError::OrbitAndPanOverlap => write!(f, "Camera orbit and pan can't use the same click"),
+        }
+    }
+}
+
+/// Mouse controls for the camera, read from camera_input.json next to the game.
+#[derive(Resource, Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraInput {
+    pub orbit: Gesture,
+    pub pan: Gesture,
+    pub orbit_sensitivity: f32,
+    pub pan_sensitivity: f32,
+    pub zoom_sensitivity: f32,
+    /// Radians the first person view turns per pixel the mouse moves.
+    pub look_sensitivity: f32,
+}
+
+impl Default for CameraInput {
+    fn default() -> Self {
+        Self {
+            orbit: Gesture { button: CameraButton::Right, modifier: None },
+            pan: Gesture { button: CameraButton::Middle, modifier: None },
+            orbit_sensitivity: 1.0,
+            pan_sensitivity: 1.0,
+            zoom_sensitivity: 1.0,
+            look_sensitivity: 0.003,
+        }
+    }
+}
+
+impl CameraInput {
+    /// Reads the settings file, a missing file just means the defaults.
+    pub fn read() -> Result<Self, CameraInputError> {
+        let contents = match fs::read_to_string(SETTINGS_PATH) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(CameraInputError::Unreadable(error.to_string())),
+        };
+
+        let input: Self = serde_json::from_str(&contents).map_err(|error| CameraInputError::Unreadable(error.to_string()))?;
+
+        input.validate()?;
+        Ok(input)
+    }
+
+    /// Checks that a single click can never both move the camera and use a tool, nor both orbit
+    /// and pan.
+    pub fn validate(&self) -> Result<(), CameraInputError> {
+        let tools = Gesture { button: CameraButton::Left, modifier: None };
+
+        if self.orbit == tools {
+            return Err(CameraInputError::ClashesWithTools("orbit"));
+        }
+
+        if self.pan == tools {
+            return Err(CameraInputError::ClashesWithTools("pan"));
+        }
+
+        if self.orbit.overlaps(&self.pan) {
+            return Err(CameraInputError::OrbitAndPanOverlap);
+        }
+
+        Ok(())
+    }
+
+    /// Whether the camera takes this click for itself.
+    fn claims(&self, button: MouseButton, keyboard_input: &ButtonInput<KeyCode>) -> bool {
+        [self.orbit, self.pan]
+            .iter()
+            .any(|gesture| MouseButton::from(gesture.button) == button && gesture.is_held(keyboard_input))
+    }
+}
+
+pub struct CameraInputPlugin;
+
+impl Plugin for CameraInputPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(CameraInput::default())
+            .add_systems(Startup, load_settings)
+            .add_systems(PreUpdate, claim_camera_clicks.after(InputSystem))
+            .add_systems(Update, (reload_settings, apply_settings));
+    }
+}
+
+fn load_settings(mut notifications: EventWriter<Notification>, mut camera_input: ResMut<CameraInput>) {
+    match CameraInput::read() {
+        Ok(input) => *camera_input = input,
+        Err(error) => notifications.send(Notification(format!("{}, using the default camera controls", error))),
+    }
+}
+
+/// F6 picks up changes to the settings file without restarting.
+fn reload_settings(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut notifications: EventWriter<Notification>,
+    mut camera_input: ResMut<CameraInput>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F6) {
+        return;
+    }
+
+    match CameraInput::read() {
+        Ok(input) => {
+            camera_input.set_if_neq(input);
+            notifications.send(Notification("Reloaded the camera controls".into()));
+        }
+        Err(error) => notifications.send(Notification(format!("{}, keeping the current camera controls", error))),
+    }
+}
+
+fn apply_settings(camera_input: Res<CameraInput>, mut camera_query: Query<&mut PanOrbitCamera>, added_query: Query<(), Added<PanOrbitCamera>>) {
+    if !camera_input.is_changed() && added_query.is_empty() {
+        return;
+    }
+
+    for mut camera in camera_query.iter_mut() {
+        camera.button_orbit = camera_input.orbit.button.into();
+        camera.modifier_orbit = camera_input.orbit.modifier.map(|modifier| modifier.key());
+        camera.button_pan = camera_input.pan.button.into();
+        camera.modifier_pan = camera_input.pan.modifier.map(|modifier| modifier.key());
+        camera.orbit_sensitivity = camera_input.orbit_sensitivity;
+        camera.pan_sensitivity = camera_input.pan_sensitivity;
+        camera.zoom_sensitivity = camera_input.zoom_sensitivity;
+    }
+}
+
+/// Hides clicks the camera uses from everything else, so a shift-click orbit with the left button
+/// doesn't also paint a tile. The camera drags for as long as the button is held, so it doesn't
+/// miss the press.
+fn claim_camera_clicks(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut mouse_button_input: ResMut<ButtonInput<MouseButton>>,
+    camera_input: Res<CameraInput>,
+) {
+    for button in [MouseButton::Left, MouseButton::Right, MouseButton::Middle] {
+        if mouse_button_input.just_pressed(button) && camera_input.claims(button, &keyboard_input) {
+            mouse_button_input.clear_just_pressed(button);
+        }
+    }
+}
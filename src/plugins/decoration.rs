@@ -0,0 +1,517 @@
+use std::{f32::consts::FRAC_PI_2, fmt};
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{economy::Wallet, hover::{cast_cursor_ray, OBSTACLE_GROUP}, notification::Notification, save::{DecorationRecord, GardenLoaded}, tile::{load_tiles, to_top_down, Tile, TileSettings, TileType}, night_light::NightLight, tools::ToolModeState, water::WATER_LEVEL};
+
+// longest stretch of water a bridge can span, in tiles
+const MAX_BRIDGE_SPAN: i32 = 6;
+// height of a bridge deck above the water
+const BRIDGE_CLEARANCE: f32 = 0.8;
+// depth below the surface pond lights sit at
+const POND_LIGHT_DEPTH: f32 = 0.3;
+// height above the surface netting is stretched at
+const NETTING_HEIGHT: f32 = 0.2;
+// brightness in lumens and reach in metres of the lights that come on at night
+const LANTERN_INTENSITY: f32 = 200_000.0;
+const LANTERN_RANGE: f32 = 15.0;
+const POND_LIGHT_INTENSITY: f32 = 300_000.0;
+const POND_LIGHT_RANGE: f32 = 12.0;
+
+/// Directions a decoration can face, one for each quarter turn.
+const FACINGS: [IVec2; 4] = [IVec2::Y, IVec2::X, IVec2::NEG_Y, IVec2::NEG_X];
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum DecorationKind {
+    StoneLantern,
+    Bridge,
+    Rock,
+    Tree,
+    PondLight,
+    /// Netting stretched over the water to keep predators out.
+    Netting,
+}
+
+impl DecorationKind {
+    const CATALOGUE: [DecorationKind; 6] = [
+        DecorationKind::StoneLantern,
+        DecorationKind::Bridge,
+        DecorationKind::Rock,
+        DecorationKind::Tree,
+        DecorationKind::PondLight,
+        DecorationKind::Netting,
+    ];
+
+    /// Overlays lie over a tile without taking it up, so they only get in the way of each other
+    /// and other decorations and equipment can share their tiles.
+    pub fn is_overlay(&self) -> bool {
+        *self == DecorationKind::Netting
+    }
+
+    pub fn price(&self) -> u32 {
+        match self {
+            DecorationKind::StoneLantern => 150,
+            DecorationKind::Bridge => 300,
+            DecorationKind::Rock => 40,
+            DecorationKind::Tree => 100,
+            DecorationKind::PondLight => 120,
+            DecorationKind::Netting => 120,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            DecorationKind::StoneLantern => Color::rgb_u8(150, 150, 140),
+            DecorationKind::Bridge => Color::rgb_u8(170, 40, 30),
+            DecorationKind::Rock => Color::rgb_u8(110, 105, 100),
+            DecorationKind::Tree => Color::rgb_u8(100, 70, 45),
+            DecorationKind::PondLight => Color::rgb_u8(60, 60, 65),
+            DecorationKind::Netting => Color::rgba_u8(60, 60, 60, 160),
+        }
+    }
+}
+
+impl fmt::Display for DecorationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecorationKind::StoneLantern => write!(f, "stone lantern"),
+            DecorationKind::Bridge => write!(f, "bridge"),
+            DecorationKind::Rock => write!(f, "rock"),
+            DecorationKind::Tree => write!(f, "tree"),
+            DecorationKind::PondLight => write!(f, "pond light"),
+            DecorationKind::Netting => write!(f, "net"),
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Decoration {
+    pub kind: DecorationKind,
+    /// Tile the decoration was placed on, bridges start on this bank.
+    pub coords: IVec2,
+    /// Number of quarter turns from facing along the grid's y axis.
+    pub rotation: u8,
+    /// Every tile the decoration stands on.
+    pub tiles: Vec<IVec2>,
+}
+
+impl Decoration {
+    pub fn covers(&self, coords: IVec2) -> bool {
+        self.tiles.contains(&coords)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DecorationError {
+    NeedsSolidGround(DecorationKind),
+    NeedsGrass(DecorationKind),
+    NeedsWater(DecorationKind),
+    NoWaterToCross,
+    TooLong,
+    Occupied,
+}
+
+impl fmt::Display for DecorationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecorationError::NeedsSolidGround(kind) => write!(f, "A {} needs solid ground", kind),
+            DecorationError::NeedsGrass(kind) => write!(f, "A {} has to be planted on grass or dirt", kind),
+            DecorationError::NeedsWater(kind) => write!(f, "A {} has to go in a pond", kind),
+            DecorationError::NoWaterToCross => write!(f, "A bridge has to start on a bank and face across water"),
+            DecorationError::TooLong => {
+                write!(f, "A bridge can't span more than {} tiles of water", MAX_BRIDGE_SPAN)
+            }
+            DecorationError::Occupied => write!(f, "There is already a decoration in the way"),
+        }
+    }
+}
+
+/// Checks that a decoration can be placed at `coords` facing `rotation` and returns the tiles it
+/// would stand on. `tile_type` looks up the type of the tile at some coordinates.
+pub fn validate_placement<'a>(
+    kind: DecorationKind,
+    coords: IVec2,
+    rotation: u8,
+    tile_type: impl Fn(IVec2) -> Option<TileType>,
+    mut placed: impl Iterator<Item = &'a Decoration>,
+) -> Result<Vec<IVec2>, DecorationError> {
+    let ground = tile_type(coords);
+    let solid = ground.is_some_and(|tile_type| tile_type != TileType::Water);
+
+    let tiles = match kind {
+        DecorationKind::StoneLantern if !solid => return Err(DecorationError::NeedsSolidGround(kind)),
+        DecorationKind::Tree if !matches!(ground, Some(TileType::Grass | TileType::Dirt)) => {
+            return Err(DecorationError::NeedsGrass(kind));
+        }
+        DecorationKind::PondLight | DecorationKind::Netting if ground != Some(TileType::Water) => {
+            return Err(DecorationError::NeedsWater(kind));
+        }
+        DecorationKind::Bridge => {
+            let facing = FACINGS[rotation as usize % FACINGS.len()];
+
+            if !solid || tile_type(coords + facing) != Some(TileType::Water) {
+                return Err(DecorationError::NoWaterToCross);
+            }
+
+            // walk across the water until the far bank
+            let mut tiles = vec![coords];
+
+            loop {
+                let next = coords + facing * tiles.len() as i32;
+
+                match tile_type(next) {
+                    Some(TileType::Water) if tiles.len() as i32 > MAX_BRIDGE_SPAN => return Err(DecorationError::TooLong),
+                    Some(TileType::Water) => tiles.push(next),
+                    Some(_) => {
+                        tiles.push(next);
+                        break tiles;
+                    }
+                    // the water runs off the edge of the garden
+                    None => return Err(DecorationError::NoWaterToCross),
+                }
+            }
+        }
+        _ => vec![coords],
+    };
+
+    if placed.any(|decoration| decoration.kind.is_overlay() == kind.is_overlay() && tiles.iter().any(|coords| decoration.covers(*coords))) {
+        return Err(DecorationError::Occupied);
+    }
+
+    Ok(tiles)
+}
+
+#[derive(Resource)]
+struct DecorationAssets {
+    lantern_mesh: Handle<Mesh>,
+    rock_mesh: Handle<Mesh>,
+    trunk_mesh: Handle<Mesh>,
+    canopy_mesh: Handle<Mesh>,
+    canopy_material: Handle<StandardMaterial>,
+    pond_light_mesh: Handle<Mesh>,
+    netting_mesh: Handle<Mesh>,
+}
+
+/// The prop the decoration tool places next, and a decoration waiting to be confirmed for removal.
+#[derive(Resource, Default)]
+struct DecorationTool {
+    catalogue_index: usize,
+    rotation: u8,
+    pending_removal: Option<Entity>,
+}
+
+impl DecorationTool {
+    fn kind(&self) -> DecorationKind {
+        DecorationKind::CATALOGUE[self.catalogue_index]
+    }
+}
+
+pub struct DecorationPlugin;
+
+impl Plugin for DecorationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(DecorationTool::default())
+            .add_systems(Startup, setup)
+            .add_systems(Update, (choose_decoration, place_decoration, load_decorations.after(load_tiles)));
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    tile_settings: Res<TileSettings>,
+) {
+    let size = tile_settings.tile_size;
+
+    commands.insert_resource(DecorationAssets {
+        lantern_mesh: meshes.add(Cuboid::new(size * 0.2, size * 0.4, size * 0.2)),
+        rock_mesh: meshes.add(Sphere::new(size * 0.25)),
+        trunk_mesh: meshes.add(Cylinder::new(size * 0.08, size * 0.6)),
+        canopy_mesh: meshes.add(Sphere::new(size * 0.35)),
+        canopy_material: materials.add(Color::rgb_u8(60, 120, 55)),
+        pond_light_mesh: meshes.add(Cylinder::new(size * 0.1, 0.1)),
+        // nets are stretched flat over the whole tile
+        netting_mesh: meshes.add(Cuboid::new(size, 0.05, size)),
+    });
+}
+
+fn spawn_decoration(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    decoration_assets: &DecorationAssets,
+    tile_settings: &TileSettings,
+    ground_height: f32,
+    decoration: Decoration,
+) {
+    let size = tile_settings.tile_size;
+    let position = tile_settings.world_position(decoration.coords);
+    let rotation = Quat::from_rotation_y(decoration.rotation as f32 * FRAC_PI_2);
+
+    let (mesh, collider, translation) = match decoration.kind {
+        DecorationKind::StoneLantern => (
+            decoration_assets.lantern_mesh.clone(),
+            Some(Collider::cuboid(size * 0.1, size * 0.2, size * 0.1)),
+            Vec3::new(position.x, ground_height + size * 0.2, position.y),
+        ),
+        DecorationKind::Rock => (
+            decoration_assets.rock_mesh.clone(),
+            Some(Collider::ball(size * 0.25)),
+            Vec3::new(position.x, ground_height, position.y),
+        ),
+        DecorationKind::Tree => (
+            decoration_assets.trunk_mesh.clone(),
+            Some(Collider::cylinder(size * 0.3, size * 0.08)),
+            Vec3::new(position.x, ground_height + size * 0.3, position.y),
+        ),
+        DecorationKind::PondLight => (
+            decoration_assets.pond_light_mesh.clone(),
+            Some(Collider::cylinder(0.05, size * 0.1)),
+            Vec3::new(position.x, WATER_LEVEL - POND_LIGHT_DEPTH, position.y),
+        ),
+        // nothing walks on the water so the net needs no collider
+        DecorationKind::Netting => (
+            decoration_assets.netting_mesh.clone(),
+            None,
+            Vec3::new(position.x, WATER_LEVEL + NETTING_HEIGHT, position.y),
+        ),
+        DecorationKind::Bridge => {
+            // the deck runs from bank to bank along the y axis before it is rotated
+            let span = decoration.tiles.len() as f32 * size;
+            let far_bank = tile_settings.world_position(decoration.tiles[decoration.tiles.len() - 1]);
+            let center = (position + far_bank) / 2.0;
+
+            (
+                meshes.add(Cuboid::new(size * 0.5, 0.3, span)),
+                Some(Collider::cuboid(size * 0.25, 0.15, span / 2.0)),
+                Vec3::new(center.x, WATER_LEVEL + BRIDGE_CLEARANCE, center.y),
+            )
+        }
+    };
+
+    let kind = decoration.kind;
+
+    let mut entity = commands.spawn((
+        PbrBundle {
+            mesh,
+            material: materials.add(kind.color()),
+            transform: Transform::from_translation(translation).with_rotation(rotation),
+            ..default()
+        },
+        decoration,
+    ));
+
+    if let Some(collider) = collider {
+        // decorations block walking but clicks go through to the tile they stand on
+        entity.insert((collider, CollisionGroups::new(OBSTACLE_GROUP, Group::ALL)));
+    }
+
+    match kind {
+        DecorationKind::Tree => {
+            entity.with_children(|builder| {
+                builder.spawn(PbrBundle {
+                    mesh: decoration_assets.canopy_mesh.clone(),
+                    material: decoration_assets.canopy_material.clone(),
+                    transform: Transform::from_xyz(0.0, size * 0.45, 0.0),
+                    ..default()
+                });
+            });
+        }
+        // lights start switched off, night_light turns them on at dusk
+        DecorationKind::StoneLantern => {
+            entity.with_children(|builder| {
+                builder.spawn((
+                    PointLightBundle {
+                        point_light: PointLight {
+                            intensity: LANTERN_INTENSITY,
+                            range: LANTERN_RANGE,
+                            color: Color::rgb(1.0, 0.75, 0.45),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0.0, size * 0.15, 0.0),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    NightLight,
+                ));
+            });
+        }
+        // shines up through the water
+        DecorationKind::PondLight => {
+            entity.with_children(|builder| {
+                builder.spawn((
+                    SpotLightBundle {
+                        spot_light: SpotLight {
+                            intensity: POND_LIGHT_INTENSITY,
+                            range: POND_LIGHT_RANGE,
+                            color: Color::rgb(0.75, 0.9, 1.0),
+                            outer_angle: 0.6,
+                            inner_angle: 0.3,
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0.0, 0.1, 0.0).looking_to(Vec3::Y, Vec3::Z),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    NightLight,
+                ));
+            });
+        }
+        DecorationKind::Bridge | DecorationKind::Rock | DecorationKind::Netting => {}
+    }
+}
+
+/// In the decoration tool C cycles through the catalogue and R turns the next decoration.
+fn choose_decoration(
+    mut notifications: EventWriter<Notification>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut tool: ResMut<DecorationTool>,
+    state: Res<State<ToolModeState>>,
+) {
+    if *state.get() != ToolModeState::Decorate {
+        return;
+    }
+
+    if state.is_changed() {
+        notifications.send(Notification(format!("Placing a {}, C: next decoration  R: rotate", tool.kind())));
+    }
+
+    if keyboard_input.just_pressed(KeyCode::KeyC) {
+        tool.catalogue_index = (tool.catalogue_index + 1) % DecorationKind::CATALOGUE.len();
+        notifications.send(Notification(format!("Placing a {} for ¥{}", tool.kind(), tool.kind().price())));
+    }
+
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        tool.rotation = (tool.rotation + 1) % FACINGS.len() as u8;
+        notifications.send(Notification(format!("Rotated the {} to {} degrees", tool.kind(), tool.rotation as u32 * 90)));
+    }
+}
+
+/// Clicking an empty tile places a decoration, clicking a decoration twice removes it.
+fn place_decoration(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut notifications: EventWriter<Notification>,
+    mut tool: ResMut<DecorationTool>,
+    mut wallet: ResMut<Wallet>,
+    rapier_context: Res<RapierContext>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    windows: Query<&Window>,
+    tile_query: Query<&Tile>,
+    decoration_query: Query<(Entity, &Decoration)>,
+    decoration_assets: Res<DecorationAssets>,
+    tile_settings: Res<TileSettings>,
+    state: Res<State<ToolModeState>>,
+) {
+    if *state.get() != ToolModeState::Decorate || !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (camera, camera_transform) = camera_query.single();
+
+    let Some((entity, _point)) = cast_cursor_ray(camera, camera_transform, windows.single(), &rapier_context) else {
+        return;
+    };
+
+    let Ok(tile) = tile_query.get(entity) else {
+        return;
+    };
+
+    let coords = tile_settings.grid_coords(to_top_down(tile.position));
+
+    let kind = tool.kind();
+
+    // overlays and the decorations under them are removed with their own kind of tool
+    if let Some((entity, decoration)) = decoration_query
+        .iter()
+        .find(|(_, decoration)| decoration.covers(coords) && decoration.kind.is_overlay() == kind.is_overlay())
+    {
+        if tool.pending_removal == Some(entity) {
+            tool.pending_removal = None;
+            commands.entity(entity).despawn_recursive();
+            notifications.send(Notification(format!("Removed the {}", decoration.kind)));
+        } else {
+            tool.pending_removal = Some(entity);
+            notifications.send(Notification(format!("Click the {} again to remove it", decoration.kind)));
+        }
+
+        return;
+    }
+
+    tool.pending_removal = None;
+
+    let tile_type = |coords: IVec2| {
+        tile_query
+            .iter()
+            .find(|tile| tile_settings.grid_coords(to_top_down(tile.position)) == coords)
+            .map(|tile| tile.tile_type)
+    };
+
+    let placed = decoration_query.iter().map(|(_, decoration)| decoration);
+
+    let tiles = match validate_placement(kind, coords, tool.rotation, tile_type, placed) {
+        Ok(tiles) => tiles,
+        Err(error) => {
+            notifications.send(Notification(error.to_string()));
+            return;
+        }
+    };
+
+    if let Err(error) = wallet.spend(kind.price()) {
+        notifications.send(Notification(error.to_string()));
+        return;
+    }
+
+    let decoration = Decoration {
+        kind,
+        coords,
+        rotation: tool.rotation,
+        tiles,
+    };
+
+    spawn_decoration(&mut commands, &mut meshes, &mut materials, &decoration_assets, &tile_settings, tile.height, decoration);
+}
+
+fn load_decorations(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut garden_loaded: EventReader<GardenLoaded>,
+    tile_query: Query<&Tile>,
+    decoration_query: Query<Entity, With<Decoration>>,
+    decoration_assets: Res<DecorationAssets>,
+    tile_settings: Res<TileSettings>,
+) {
+    let Some(GardenLoaded(save)) = garden_loaded.read().last() else {
+        return;
+    };
+
+    for entity in decoration_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    for DecorationRecord { kind, coords, rotation, tiles } in save.decorations.iter() {
+        let coords = IVec2::from(*coords);
+
+        // tiles have already been restored from the same save
+        let ground_height = tile_query
+            .iter()
+            .find(|tile| tile_settings.grid_coords(to_top_down(tile.position)) == coords)
+            .map_or(WATER_LEVEL, |tile| tile.height);
+
+        let decoration = Decoration {
+            kind: *kind,
+            coords,
+            rotation: *rotation,
+            tiles: tiles.iter().copied().map(IVec2::from).collect(),
+        };
+
+        spawn_decoration(&mut commands, &mut meshes, &mut materials, &decoration_assets, &tile_settings, ground_height, decoration);
+    }
+}
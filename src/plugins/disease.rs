@@ -0,0 +1,394 @@
+use std::{collections::HashMap, fmt};
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::{seq::IteratorRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::{hover::{cast_cursor_ray, HoveredKoi}, koi::Koi, notification::Notification, pond::{detect_ponds, Pond, Ponds}, save::GardenLoaded, tile::{to_top_down, Tile, TileSettings}, tools::ToolModeState};
+
+// real seconds between disease ticks
+const DISEASE_TICK: f32 = 10.0;
+// chance per tick a treated koi shakes off its illness
+const CURE_CHANCE: f64 = 0.3;
+// treatment works better away from the stress of a busy pond
+const QUARANTINE_CURE_BONUS: f64 = 1.5;
+// ticks a dose of salt stays effective
+const SALT_DURATION: u32 = 12;
+const LOW_OXYGEN: f32 = 5.0;
+const HIGH_AMMONIA: f32 = 0.5;
+const INDICATOR_HEIGHT: f32 = 0.6;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum Illness {
+    FinRot,
+    Ich,
+}
+
+impl Illness {
+    const ALL: [Illness; 2] = [Illness::FinRot, Illness::Ich];
+
+    /// Chance per tick a fully stressed koi falls ill without catching it from another.
+    fn outbreak_chance(&self) -> f64 {
+        match self {
+            Illness::FinRot => 0.02,
+            Illness::Ich => 0.03,
+        }
+    }
+
+    /// Chance per tick each sick koi passes the illness to a healthy koi in the same pond.
+    fn spread_chance(&self) -> f64 {
+        match self {
+            Illness::FinRot => 0.05,
+            Illness::Ich => 0.1,
+        }
+    }
+
+    /// Health lost per tick.
+    fn damage(&self) -> f32 {
+        match self {
+            Illness::FinRot => 0.03,
+            Illness::Ich => 0.02,
+        }
+    }
+
+    fn treated_by(&self, treatment: Treatment) -> bool {
+        match (self, treatment) {
+            (Illness::Ich, Treatment::Salt) => true,
+            (_, Treatment::Medication) => true,
+            _ => false,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Illness::FinRot => Color::rgb_u8(150, 60, 40),
+            Illness::Ich => Color::WHITE,
+        }
+    }
+}
+
+impl fmt::Display for Illness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Illness::FinRot => write!(f, "fin rot"),
+            Illness::Ich => write!(f, "ich"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Treatment {
+    Salt,
+    Medication,
+}
+
+#[derive(Component, Debug)]
+pub struct Sick {
+    pub illness: Illness,
+    pub medicated: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct PondTreatments {
+    // ticks of salt left in each pond
+    salted: HashMap<u32, u32>,
+    pub quarantine: Option<u32>,
+}
+
+impl PondTreatments {
+    pub fn is_salted(&self, pond_id: u32) -> bool {
+        self.salted.contains_key(&pond_id)
+    }
+
+    /// Disease ticks the salt in a pond keeps working for.
+    pub fn salt_left(&self, pond_id: u32) -> u32 {
+        self.salted.get(&pond_id).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Resource)]
+struct DiseaseTimer(Timer);
+
+#[derive(Component)]
+struct SymptomIndicator {
+    koi: Entity,
+}
+
+pub struct DiseasePlugin;
+
+impl Plugin for DiseasePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(PondTreatments::default())
+            .insert_resource(DiseaseTimer(Timer::from_seconds(DISEASE_TICK, TimerMode::Repeating)))
+            .add_systems(Update, (progress_disease, apply_salt, medicate, quarantine, show_symptoms, follow_symptoms, load_treatments.after(detect_ponds)));
+    }
+}
+
+/// How strongly a pond's water and crowding push its koi towards illness, from 0.0 to 1.0.
+fn pond_stress(pond: &Pond, koi_count: usize) -> f64 {
+    let chemistry = &pond.chemistry;
+    let oxygen = ((LOW_OXYGEN - chemistry.oxygen) / LOW_OXYGEN).max(0.0);
+    let ammonia = chemistry.ammonia / HIGH_AMMONIA;
+    let murk = (1.0 - chemistry.clarity) * 0.5;
+    let crowding = koi_count as f32 / pond.fish_capacity().max(1) as f32;
+
+    (oxygen + ammonia + murk + crowding * crowding).clamp(0.0, 1.0) as f64
+}
+
+fn progress_disease(
+    mut commands: Commands,
+    mut notifications: EventWriter<Notification>,
+    time: Res<Time>,
+    mut timer: ResMut<DiseaseTimer>,
+    mut treatments: ResMut<PondTreatments>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+    mut koi_query: Query<(Entity, &mut Koi, &Transform, Option<&Sick>)>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut pond_koi: HashMap<u32, Vec<Entity>> = HashMap::new();
+    let mut sick_counts: HashMap<(u32, Illness), i32> = HashMap::new();
+
+    for (entity, _, transform, sick) in koi_query.iter() {
+        if let Some(pond) = ponds.pond_at(tile_settings.grid_coords(to_top_down(transform.translation))) {
+            pond_koi.entry(pond.id).or_default().push(entity);
+
+            if let Some(sick) = sick {
+                *sick_counts.entry((pond.id, sick.illness)).or_default() += 1;
+            }
+        }
+    }
+
+    for (pond_id, entities) in pond_koi {
+        let Some(pond) = ponds.get(pond_id) else {
+            continue;
+        };
+
+        let stress = pond_stress(pond, entities.len());
+        let quarantined = treatments.quarantine == Some(pond_id);
+
+        for entity in entities {
+            let Ok((_, mut koi, _, sick)) = koi_query.get_mut(entity) else {
+                continue;
+            };
+
+            let Some(sick) = sick else {
+                for illness in Illness::ALL {
+                    let sick_count = sick_counts.get(&(pond_id, illness)).copied().unwrap_or_default();
+                    let healthy_chance = (1.0 - illness.outbreak_chance() * stress) * (1.0 - illness.spread_chance()).powi(sick_count);
+
+                    if rng.gen_bool(1.0 - healthy_chance) {
+                        notifications.send(Notification(format!("{} has caught {}", koi.name, illness)));
+                        commands.entity(entity).insert(Sick {
+                            illness,
+                            medicated: false,
+                        });
+                        break;
+                    }
+                }
+
+                continue;
+            };
+
+            let treated = (sick.medicated && sick.illness.treated_by(Treatment::Medication))
+                || (treatments.is_salted(pond_id) && sick.illness.treated_by(Treatment::Salt));
+            let cure_chance = if quarantined { CURE_CHANCE * QUARANTINE_CURE_BONUS } else { CURE_CHANCE };
+
+            if treated && rng.gen_bool(cure_chance.min(1.0)) {
+                notifications.send(Notification(format!("{} has recovered from {}", koi.name, sick.illness)));
+                commands.entity(entity).remove::<Sick>();
+            } else {
+                koi.health = (koi.health - sick.illness.damage()).max(0.0);
+            }
+        }
+    }
+
+    treatments.salted.retain(|_, ticks| {
+        *ticks -= 1;
+        *ticks > 0
+    });
+}
+
+fn apply_salt(
+    mut notifications: EventWriter<Notification>,
+    mut treatments: ResMut<PondTreatments>,
+    rapier_context: Res<RapierContext>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    windows: Query<&Window>,
+    tile_query: Query<&Tile>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+    state: Res<State<ToolModeState>>,
+) {
+    if *state.get() != ToolModeState::Salt || !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (camera, camera_transform) = camera_query.single();
+
+    let Some((entity, _point)) = cast_cursor_ray(camera, camera_transform, windows.single(), &rapier_context) else {
+        return;
+    };
+
+    let Some(pond) = tile_query
+        .get(entity)
+        .ok()
+        .and_then(|tile| ponds.pond_at(tile_settings.grid_coords(to_top_down(tile.position)))) else {
+        notifications.send(Notification("Salt must be added to a pond".into()));
+        return;
+    };
+
+    treatments.salted.insert(pond.id, SALT_DURATION);
+    notifications.send(Notification(format!("Added salt to pond {}", pond.id)));
+}
+
+fn medicate(
+    mut notifications: EventWriter<Notification>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    hovered_koi: Res<HoveredKoi>,
+    mut koi_query: Query<(&Koi, Option<&mut Sick>)>,
+    state: Res<State<ToolModeState>>,
+) {
+    if *state.get() != ToolModeState::Medicate || !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(entity) = hovered_koi.0 else {
+        notifications.send(Notification("Click on a koi to give it medication".into()));
+        return;
+    };
+
+    let Ok((koi, sick)) = koi_query.get_mut(entity) else {
+        return;
+    };
+
+    match sick {
+        Some(mut sick) => {
+            sick.medicated = true;
+            notifications.send(Notification(format!("Gave {} medication for {}", koi.name, sick.illness)));
+        }
+        None => {
+            notifications.send(Notification(format!("{} is not sick", koi.name)));
+        }
+    }
+}
+
+/// Clicking a koi nets it into the quarantine pond, clicking a pond makes it the quarantine pond.
+fn quarantine(
+    mut notifications: EventWriter<Notification>,
+    mut treatments: ResMut<PondTreatments>,
+    rapier_context: Res<RapierContext>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    windows: Query<&Window>,
+    hovered_koi: Res<HoveredKoi>,
+    mut koi_query: Query<(&mut Koi, &mut Transform)>,
+    tile_query: Query<&Tile>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+    state: Res<State<ToolModeState>>,
+) {
+    if *state.get() != ToolModeState::Quarantine || !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    if let Some(entity) = hovered_koi.0 {
+        let Ok((mut koi, mut transform)) = koi_query.get_mut(entity) else {
+            return;
+        };
+
+        let Some(pond) = treatments.quarantine.and_then(|id| ponds.get(id)) else {
+            notifications.send(Notification("Click on a pond to make it the quarantine pond first".into()));
+            return;
+        };
+
+        let Some(coords) = pond.tiles.iter().choose(&mut rand::thread_rng()) else {
+            return;
+        };
+
+        koi.relocate(&mut *transform, tile_settings.world_position(*coords));
+        notifications.send(Notification(format!("Moved {} to the quarantine pond", koi.name)));
+        return;
+    }
+
+    let (camera, camera_transform) = camera_query.single();
+
+    let Some(pond) = cast_cursor_ray(camera, camera_transform, windows.single(), &rapier_context)
+        .and_then(|(entity, _point)| tile_query.get(entity).ok())
+        .and_then(|tile| ponds.pond_at(tile_settings.grid_coords(to_top_down(tile.position)))) else {
+        notifications.send(Notification("Click on a pond to quarantine it or on a koi to move it there".into()));
+        return;
+    };
+
+    treatments.quarantine = Some(pond.id);
+    notifications.send(Notification(format!("Pond {} is now the quarantine pond", pond.id)));
+}
+
+/// Restores the salt in each pond and the quarantine pond of a loaded garden.
+fn load_treatments(mut garden_loaded: EventReader<GardenLoaded>, mut treatments: ResMut<PondTreatments>, ponds: Res<Ponds>) {
+    let Some(GardenLoaded(save)) = garden_loaded.read().last() else {
+        return;
+    };
+
+    treatments.salted.clear();
+    treatments.quarantine = None;
+
+    for record in save.ponds.iter() {
+        let Some(pond) = ponds.pond_at(IVec2::from(record.tile)) else {
+            continue;
+        };
+
+        if record.salt > 0 {
+            treatments.salted.insert(pond.id, record.salt);
+        }
+
+        if record.quarantine {
+            treatments.quarantine = Some(pond.id);
+        }
+    }
+}
+
+fn show_symptoms(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    sick_query: Query<(Entity, &Sick), Added<Sick>>,
+) {
+    for (entity, sick) in sick_query.iter() {
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(Sphere::new(0.1)),
+                material: materials.add(sick.illness.color()),
+                ..default()
+            },
+            SymptomIndicator {
+                koi: entity,
+            },
+        ));
+    }
+}
+
+fn follow_symptoms(
+    mut commands: Commands,
+    koi_query: Query<&Transform, (With<Sick>, Without<SymptomIndicator>)>,
+    mut indicator_query: Query<(Entity, &SymptomIndicator, &mut Transform)>,
+) {
+    for (entity, indicator, mut transform) in indicator_query.iter_mut() {
+        match koi_query.get(indicator.koi) {
+            Ok(koi_transform) => {
+                transform.translation = koi_transform.translation + Vec3::Y * INDICATOR_HEIGHT;
+            }
+            // the koi recovered or died
+            Err(_) => {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
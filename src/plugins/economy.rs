@@ -0,0 +1,169 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use super::{genome::Variety, hover::HoveredKoi, koi::Koi, notification::Notification, save::GardenLoaded, show::judge, tools::ToolModeState};
+
+const STARTING_BALANCE: u32 = 5000;
+// price of a koi of unknown quality released with the koi tool
+pub const KOI_PRICE: u32 = 150;
+// sale value of each show point a koi scores
+const VALUE_PER_POINT: f32 = 4.0;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PurchaseError {
+    InsufficientFunds { cost: u32, balance: u32 },
+}
+
+impl fmt::Display for PurchaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PurchaseError::InsufficientFunds { cost, balance } => {
+                write!(f, "That costs ¥{} but you only have ¥{}", cost, balance)
+            }
+        }
+    }
+}
+
+/// The player's money, nothing costs anything in sandbox mode.
+#[derive(Resource, Debug)]
+pub struct Wallet {
+    balance: u32,
+    pub sandbox: bool,
+}
+
+impl Default for Wallet {
+    fn default() -> Self {
+        Self {
+            balance: STARTING_BALANCE,
+            sandbox: false,
+        }
+    }
+}
+
+impl Wallet {
+    pub fn balance(&self) -> u32 {
+        self.balance
+    }
+
+    /// Takes `cost` from the balance, or leaves it untouched if the player can't afford it.
+    pub fn spend(&mut self, cost: u32) -> Result<(), PurchaseError> {
+        if self.sandbox {
+            return Ok(());
+        }
+
+        self.balance = self
+            .balance
+            .checked_sub(cost)
+            .ok_or(PurchaseError::InsufficientFunds { cost, balance: self.balance })?;
+
+        Ok(())
+    }
+
+    pub fn earn(&mut self, amount: u32) {
+        self.balance = self.balance.saturating_add(amount);
+    }
+}
+
+/// How much more buyers pay for a variety than for an ordinary koi of the same quality.
+fn variety_premium(variety: Variety) -> f32 {
+    match variety {
+        Variety::Kohaku | Variety::Sanke | Variety::Showa => 1.5,
+        Variety::Ogon => 1.2,
+        Variety::ShiroMuji | Variety::Karasu | Variety::Asagi | Variety::Chagoi => 1.0,
+    }
+}
+
+/// What a buyer will pay for a koi, judged the same way as at a koi show.
+pub fn koi_value(koi: &Koi) -> u32 {
+    (judge(koi).total * VALUE_PER_POINT * variety_premium(koi.variety())).round() as u32
+}
+
+#[derive(Component)]
+struct BalanceDisplay;
+
+pub struct EconomyPlugin;
+
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Wallet::default())
+            .add_systems(Startup, setup)
+            .add_systems(Update, (toggle_sandbox, sell_koi, display_balance, load_wallet));
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 30.0,
+                color: Color::rgb_u8(255, 220, 120),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.),
+            left: Val::Percent(45.),
+            ..default()
+        }),
+        BalanceDisplay,
+    ));
+}
+
+fn toggle_sandbox(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut wallet: ResMut<Wallet>,
+    mut notifications: EventWriter<Notification>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F1) {
+        wallet.sandbox = !wallet.sandbox;
+
+        let message = if wallet.sandbox { "Sandbox mode on, everything is free" } else { "Sandbox mode off" };
+        notifications.send(Notification(message.into()));
+    }
+}
+
+fn sell_koi(
+    mut commands: Commands,
+    mut notifications: EventWriter<Notification>,
+    mut wallet: ResMut<Wallet>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    hovered_koi: Res<HoveredKoi>,
+    koi_query: Query<&Koi>,
+    state: Res<State<ToolModeState>>,
+) {
+    if *state.get() != ToolModeState::Sell || !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some((entity, koi)) = hovered_koi.0.and_then(|entity| koi_query.get(entity).ok().map(|koi| (entity, koi))) else {
+        notifications.send(Notification("Click on a koi to sell it".into()));
+        return;
+    };
+
+    let value = koi_value(koi);
+
+    wallet.earn(value);
+    commands.entity(entity).despawn_recursive();
+    notifications.send(Notification(format!("Sold {} the {} for ¥{}", koi.name, koi.variety(), value)));
+}
+
+fn load_wallet(mut garden_loaded: EventReader<GardenLoaded>, mut wallet: ResMut<Wallet>) {
+    if let Some(GardenLoaded(save)) = garden_loaded.read().last() {
+        wallet.balance = save.balance;
+        wallet.sandbox = save.sandbox;
+    }
+}
+
+fn display_balance(wallet: Res<Wallet>, mut text_query: Query<&mut Text, With<BalanceDisplay>>) {
+    if !wallet.is_changed() {
+        return;
+    }
+
+    let mut text = text_query.single_mut();
+
+    text.sections[0].value = if wallet.sandbox { "Sandbox".into() } else { format!("¥{}", wallet.balance()) };
+}
@@ -0,0 +1,299 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{economy::Wallet, hover::cast_cursor_ray, notification::Notification, pond::{detect_ponds, Ponds}, save::GardenLoaded, tile::{load_tiles, to_top_down, Tile, TileSettings, TileType}, tools::ToolModeState, water::WATER_LEVEL};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum EquipmentType {
+    Pump,
+    Filter,
+    Aerator,
+}
+
+impl EquipmentType {
+    /// Pond volume in cubic metres a single unit is rated to handle.
+    pub fn rated_volume(&self) -> f32 {
+        match self {
+            EquipmentType::Pump => 200.0,
+            EquipmentType::Filter => 150.0,
+            EquipmentType::Aerator => 100.0,
+        }
+    }
+
+    pub fn price(&self) -> u32 {
+        match self {
+            EquipmentType::Pump => 400,
+            EquipmentType::Filter => 350,
+            EquipmentType::Aerator => 200,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            EquipmentType::Pump => Color::rgb_u8(90, 96, 104),
+            EquipmentType::Filter => Color::rgb_u8(52, 74, 58),
+            EquipmentType::Aerator => Color::rgb_u8(210, 214, 220),
+        }
+    }
+
+    fn from_tool(tool: &ToolModeState) -> Option<Self> {
+        match tool {
+            ToolModeState::Pump => Some(EquipmentType::Pump),
+            ToolModeState::Filter => Some(EquipmentType::Filter),
+            ToolModeState::Aerator => Some(EquipmentType::Aerator),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for EquipmentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EquipmentType::Pump => write!(f, "pump"),
+            EquipmentType::Filter => write!(f, "filter"),
+            EquipmentType::Aerator => write!(f, "aerator"),
+        }
+    }
+}
+
+// share of a filter's capacity lost per second as it fills with muck
+const CLOGGING_RATE: f32 = 1.0 / 600.0;
+// a fully clogged filter still lets some water through
+const MAX_CLOGGING: f32 = 0.8;
+
+#[derive(Component, Debug)]
+pub struct Equipment {
+    pub equipment_type: EquipmentType,
+    pub coords: IVec2,
+    /// How clogged a filter is, from 0.0 (clean) to MAX_CLOGGING.
+    pub clogging: f32,
+}
+
+impl Equipment {
+    /// Pond volume the equipment can currently handle.
+    pub fn capacity(&self) -> f32 {
+        self.equipment_type.rated_volume() * (1.0 - self.clogging)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PlacementError {
+    NoPond(EquipmentType),
+    Occupied,
+}
+
+impl fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlacementError::NoPond(equipment_type) => {
+                write!(f, "A {} must be placed in a pond or on a tile next to one", equipment_type)
+            }
+            PlacementError::Occupied => write!(f, "There is already equipment on this tile"),
+        }
+    }
+}
+
+/// Checks that equipment can go on the tile at `coords` and returns the id of the pond it serves.
+pub fn validate_placement<'a>(
+    equipment_type: EquipmentType,
+    coords: IVec2,
+    ponds: &Ponds,
+    mut placed: impl Iterator<Item = &'a Equipment>,
+) -> Result<u32, PlacementError> {
+    if placed.any(|equipment| equipment.coords == coords) {
+        return Err(PlacementError::Occupied);
+    }
+
+    ponds
+        .pond_touching(coords)
+        .map(|pond| pond.id)
+        .ok_or(PlacementError::NoPond(equipment_type))
+}
+
+pub struct EquipmentPlugin;
+
+impl Plugin for EquipmentPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, (place_equipment, clog_filters, load_equipment.after(load_tiles), settle_equipment.after(detect_ponds)));
+    }
+}
+
+fn place_equipment(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut notifications: EventWriter<Notification>,
+    mut wallet: ResMut<Wallet>,
+    rapier_context: Res<RapierContext>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    windows: Query<&Window>,
+    tile_query: Query<&Tile>,
+    equipment_query: Query<&Equipment>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+    state: Res<State<ToolModeState>>,
+) {
+    let Some(equipment_type) = EquipmentType::from_tool(state.get()) else {
+        return;
+    };
+
+    if !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (camera, camera_transform) = camera_query.single();
+
+    let Some((entity, _point)) = cast_cursor_ray(camera, camera_transform, windows.single(), &rapier_context) else {
+        return;
+    };
+
+    let Ok(tile) = tile_query.get(entity) else {
+        return;
+    };
+
+    let coords = tile_settings.grid_coords(to_top_down(tile.position));
+
+    let pond_id = match validate_placement(equipment_type, coords, &ponds, equipment_query.iter()) {
+        Ok(pond_id) => pond_id,
+        Err(error) => {
+            notifications.send(Notification(error.to_string()));
+            return;
+        }
+    };
+
+    if let Err(error) = wallet.spend(equipment_type.price()) {
+        notifications.send(Notification(error.to_string()));
+        return;
+    }
+
+    let equipment = Equipment {
+        equipment_type,
+        coords,
+        clogging: 0.0,
+    };
+
+    spawn_equipment(&mut commands, &mut meshes, &mut materials, &tile_settings, tile, equipment);
+
+    notifications.send(Notification(format!("Installed a {} in pond {}", equipment_type, pond_id)));
+}
+
+fn spawn_equipment(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    tile_settings: &TileSettings,
+    tile: &Tile,
+    equipment: Equipment,
+) {
+    let shape = equipment_shape(tile_settings);
+
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(shape),
+            material: materials.add(equipment.equipment_type.color()),
+            transform: Transform::from_xyz(tile.position.x, resting_height(tile, &shape), tile.position.z),
+            ..default()
+        },
+        equipment,
+    ));
+}
+
+fn equipment_shape(tile_settings: &TileSettings) -> Cuboid {
+    let size = tile_settings.tile_size * 0.3;
+
+    Cuboid::new(size, size, size)
+}
+
+/// Height of the centre of equipment resting on `tile`, on the water surface in a pond and on
+/// top of the tile on the bank.
+fn resting_height(tile: &Tile, shape: &Cuboid) -> f32 {
+    let surface = if tile.tile_type == TileType::Water { WATER_LEVEL } else { tile.height };
+
+    surface + shape.half_size.y
+}
+
+/// Keeps equipment in step with repainted tiles. Equipment whose tile changed moves onto the new
+/// surface, and equipment no longer touching any pond is taken out and refunded.
+fn settle_equipment(
+    mut commands: Commands,
+    mut notifications: EventWriter<Notification>,
+    mut wallet: ResMut<Wallet>,
+    mut garden_loaded: EventReader<GardenLoaded>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+    changed_query: Query<&Tile, Changed<Tile>>,
+    mut equipment_query: Query<(Entity, &Equipment, &mut Transform)>,
+) {
+    // a loaded garden brings its own equipment, nothing about to be replaced gets refunded
+    if garden_loaded.read().last().is_some() || changed_query.is_empty() {
+        return;
+    }
+
+    for (entity, equipment, mut transform) in equipment_query.iter_mut() {
+        if ponds.pond_touching(equipment.coords).is_none() {
+            commands.entity(entity).despawn();
+            wallet.earn(equipment.equipment_type.price());
+            notifications.send(Notification(format!(
+                "Took out the {} with its pond gone, refunded ¥{}",
+                equipment.equipment_type,
+                equipment.equipment_type.price(),
+            )));
+            continue;
+        }
+
+        let Some(tile) = changed_query.iter().find(|tile| tile_settings.grid_coords(to_top_down(tile.position)) == equipment.coords) else {
+            continue;
+        };
+
+        transform.translation.y = resting_height(tile, &equipment_shape(&tile_settings));
+    }
+}
+
+fn clog_filters(time: Res<Time>, mut equipment_query: Query<&mut Equipment>) {
+    for mut equipment in equipment_query.iter_mut() {
+        if equipment.equipment_type == EquipmentType::Filter {
+            equipment.clogging = (equipment.clogging + CLOGGING_RATE * time.delta_seconds()).min(MAX_CLOGGING);
+        }
+    }
+}
+
+fn load_equipment(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut garden_loaded: EventReader<GardenLoaded>,
+    tile_query: Query<&Tile>,
+    equipment_query: Query<Entity, With<Equipment>>,
+    tile_settings: Res<TileSettings>,
+) {
+    let Some(GardenLoaded(save)) = garden_loaded.read().last() else {
+        return;
+    };
+
+    for entity in equipment_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for record in save.equipment.iter() {
+        let coords = IVec2::from(record.coords);
+
+        let Some(tile) = tile_query
+            .iter()
+            .find(|tile| tile_settings.grid_coords(to_top_down(tile.position)) == coords) else {
+            continue;
+        };
+
+        let equipment = Equipment {
+            equipment_type: record.equipment_type,
+            coords,
+            clogging: record.clogging,
+        };
+
+        spawn_equipment(&mut commands, &mut meshes, &mut materials, &tile_settings, tile, equipment);
+    }
+}
@@ -0,0 +1,231 @@
+use std::{cmp::Ordering, f32::consts::PI};
+
+use bevy::{input::mouse::MouseMotion, prelude::*, transform::TransformSystem, window::CursorGrabMode};
+use bevy_panorbit_camera::PanOrbitCamera;
+use bevy_rapier3d::prelude::*;
+
+use super::{camera_controller::CameraMode, camera_input::CameraInput, notification::Notification, tile::{to_top_down, Tile, TileSettings, TileType}, tools::ToolModeState, visitor::GATE};
+
+// the walker is about as tall as a visitor and sees from just below the top of their head
+const BODY_HEIGHT: f32 = 1.8;
+const BODY_RADIUS: f32 = 0.3;
+const EYE_HEIGHT: f32 = 1.6;
+// metres per second
+const WALK_SPEED: f32 = 3.0;
+const RUN_SPEED: f32 = 7.0;
+const FALL_SPEED: f32 = 9.8;
+// highest ledge the walker steps up onto, enough for the step from dirt onto grass or a bridge
+const STEP_HEIGHT: f32 = 0.6;
+// stops the view flipping over when looking straight up or down
+const MAX_LOOK_PITCH: f32 = 1.4;
+
+/// The body walking around the garden in first person, the camera rides along at eye height.
+#[derive(Component, Debug, Default)]
+struct Walker {
+    yaw: f32,
+    pitch: f32,
+}
+
+pub struct FirstPersonPlugin;
+
+impl Plugin for FirstPersonPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(CameraMode::FirstPerson), enter_first_person)
+            .add_systems(OnExit(CameraMode::FirstPerson), exit_first_person)
+            .add_systems(Update, toggle_first_person)
+            .add_systems(Update, (look, walk).chain().run_if(in_state(CameraMode::FirstPerson)))
+            .add_systems(
+                PostUpdate,
+                follow_walker
+                    .after(PhysicsSet::Writeback)
+                    .before(TransformSystem::TransformPropagate)
+                    .run_if(in_state(CameraMode::FirstPerson)),
+            );
+    }
+}
+
+fn toggle_first_person(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    state: Res<State<CameraMode>>,
+    mut next_state: ResMut<NextState<CameraMode>>,
+) {
+    let walking = *state.get() == CameraMode::FirstPerson;
+
+    if keyboard_input.just_pressed(KeyCode::KeyV) {
+        next_state.set(if walking { CameraMode::Orbit } else { CameraMode::FirstPerson });
+    }
+
+    if walking && keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(CameraMode::Orbit);
+    }
+}
+
+fn enter_first_person(
+    mut commands: Commands,
+    mut notifications: EventWriter<Notification>,
+    mut next_state: ResMut<NextState<CameraMode>>,
+    mut next_tool_state: ResMut<NextState<ToolModeState>>,
+    mut camera_query: Query<&mut PanOrbitCamera>,
+    mut windows: Query<&mut Window>,
+    tile_query: Query<&Tile>,
+    tile_settings: Res<TileSettings>,
+) {
+    let mut camera = camera_query.single_mut();
+    let focus = to_top_down(camera.target_focus);
+
+    // start at the gate like a visitor, or if it has been dug out on the ground nearest the view
+    let preference = |tile: &Tile| {
+        let position = to_top_down(tile.position);
+
+        (tile_settings.grid_coords(position) != GATE, position.distance(focus))
+    };
+
+    let start = tile_query
+        .iter()
+        .filter(|tile| tile.tile_type.walk_cost().is_some())
+        .min_by(|a, b| preference(a).partial_cmp(&preference(b)).unwrap_or(Ordering::Equal));
+
+    let Some(start) = start else {
+        notifications.send(Notification("There is no dry ground to walk on".into()));
+        next_state.set(CameraMode::Orbit);
+        return;
+    };
+
+    let position = to_top_down(start.position);
+
+    commands.spawn((
+        TransformBundle::from_transform(Transform::from_xyz(position.x, start.height + BODY_HEIGHT / 2.0, position.y)),
+        RigidBody::KinematicPositionBased,
+        Collider::capsule_y(BODY_HEIGHT / 2.0 - BODY_RADIUS, BODY_RADIUS),
+        KinematicCharacterController {
+            autostep: Some(CharacterAutostep {
+                max_height: CharacterLength::Absolute(STEP_HEIGHT),
+                min_width: CharacterLength::Absolute(BODY_RADIUS),
+                include_dynamic_bodies: false,
+            }),
+            snap_to_ground: Some(CharacterLength::Absolute(STEP_HEIGHT)),
+            // walk straight through koi and anything else that is only a sensor
+            filter_flags: QueryFilterFlags::EXCLUDE_SENSORS,
+            ..default()
+        },
+        // facing into the garden from the gate
+        Walker { yaw: PI, pitch: 0.0 },
+    ));
+
+    // the orbit camera keeps its view while switched off and picks it up again afterwards
+    camera.enabled = false;
+
+    // walking is for looking, the tools come back with the orbit camera
+    next_tool_state.set(ToolModeState::None);
+
+    let mut window = windows.single_mut();
+    window.cursor.grab_mode = CursorGrabMode::Locked;
+    window.cursor.visible = false;
+
+    notifications.send(Notification("WASD: walk  Shift: run  Mouse: look  V or Esc: stop walking".into()));
+}
+
+fn exit_first_person(
+    mut commands: Commands,
+    mut camera_query: Query<&mut PanOrbitCamera>,
+    mut windows: Query<&mut Window>,
+    walker_query: Query<(Entity, &Transform), With<Walker>>,
+) {
+    let mut camera = camera_query.single_mut();
+
+    // look down on wherever the walk ended
+    for (entity, transform) in walker_query.iter() {
+        camera.target_focus = transform.translation;
+        commands.entity(entity).despawn_recursive();
+    }
+
+    camera.enabled = true;
+    camera.force_update = true;
+
+    let mut window = windows.single_mut();
+    window.cursor.grab_mode = CursorGrabMode::None;
+    window.cursor.visible = true;
+}
+
+fn look(mut mouse_motion: EventReader<MouseMotion>, camera_input: Res<CameraInput>, mut walker_query: Query<&mut Walker>) {
+    let delta: Vec2 = mouse_motion.read().map(|motion| motion.delta).sum::<Vec2>() * camera_input.look_sensitivity;
+
+    for mut walker in walker_query.iter_mut() {
+        walker.yaw -= delta.x;
+        walker.pitch = (walker.pitch - delta.y).clamp(-MAX_LOOK_PITCH, MAX_LOOK_PITCH);
+    }
+}
+
+fn walk(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    rapier_context: Res<RapierContext>,
+    tile_query: Query<&Tile>,
+    mut walker_query: Query<(Entity, &Walker, &Transform, &mut KinematicCharacterController)>,
+) {
+    let Ok((entity, walker, transform, mut controller)) = walker_query.get_single_mut() else {
+        return;
+    };
+
+    let mut input = Vec2::ZERO;
+
+    if keyboard_input.pressed(KeyCode::KeyW) {
+        input.y += 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::KeyS) {
+        input.y -= 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::KeyD) {
+        input.x += 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::KeyA) {
+        input.x -= 1.0;
+    }
+
+    let speed = if keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) { RUN_SPEED } else { WALK_SPEED };
+
+    // forward is along -z before turning, the same way the camera looks
+    let rotation = Quat::from_rotation_y(walker.yaw);
+    let forward = rotation * Vec3::NEG_Z;
+    let right = rotation * Vec3::X;
+    let step = (forward * input.y + right * input.x).normalize_or_zero() * speed * time.delta_seconds();
+
+    // the character controller stops at decorations and banks, but water is lower than the ground
+    // around it so it would happily walk down into a pond, check for that first and slide along the
+    // edge instead
+    let dry = |step: Vec3| {
+        let ahead = transform.translation + step + step.normalize_or_zero() * BODY_RADIUS;
+
+        rapier_context
+            .cast_ray(
+                ahead,
+                Vec3::NEG_Y,
+                f32::MAX,
+                true,
+                QueryFilter::default().exclude_sensors().exclude_collider(entity),
+            )
+            .is_some_and(|(hit, _toi)| tile_query.get(hit).map_or(true, |tile| tile.tile_type != TileType::Water))
+    };
+
+    let step = [step, Vec3::new(step.x, 0.0, 0.0), Vec3::new(0.0, 0.0, step.z)]
+        .into_iter()
+        .find(|step| *step != Vec3::ZERO && dry(*step))
+        .unwrap_or(Vec3::ZERO);
+
+    controller.translation = Some(step + Vec3::NEG_Y * FALL_SPEED * time.delta_seconds());
+}
+
+fn follow_walker(
+    walker_query: Query<(&Walker, &Transform), Without<Camera3d>>,
+    mut camera_query: Query<&mut Transform, With<Camera3d>>,
+) {
+    let Ok((walker, walker_transform)) = walker_query.get_single() else {
+        return;
+    };
+
+    let mut camera_transform = camera_query.single_mut();
+
+    camera_transform.translation = walker_transform.translation + Vec3::Y * (EYE_HEIGHT - BODY_HEIGHT / 2.0);
+    camera_transform.rotation = Quat::from_euler(EulerRot::YXZ, walker.yaw, walker.pitch, 0.0);
+}
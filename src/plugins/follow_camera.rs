@@ -0,0 +1,190 @@
+use bevy::prelude::*;
+use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraSystemSet};
+
+use super::{camera_controller::CameraMode, hover::{HoveredKoi, SelectedKoi}, koi::Koi, notification::Notification, tile::TileSettings, water::WATER_LEVEL};
+
+// seconds spent on each koi during the tour
+const TOUR_INTERVAL: f32 = 15.0;
+// looking down into the water at a steep angle so the surface doesn't hide the fish
+const FOLLOW_PITCH: f32 = 0.9;
+// how many tiles back the camera sits from the koi it follows
+const FOLLOW_DISTANCE: f32 = 2.0;
+
+/// The koi the camera follows, and whether it moves on to another koi every so often.
+#[derive(Resource)]
+struct FollowTarget {
+    koi: Option<Entity>,
+    tour: bool,
+    timer: Timer,
+}
+
+impl Default for FollowTarget {
+    fn default() -> Self {
+        Self {
+            koi: None,
+            tour: false,
+            timer: Timer::from_seconds(TOUR_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+pub struct FollowCameraPlugin;
+
+impl Plugin for FollowCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(FollowTarget::default())
+            .add_systems(OnEnter(CameraMode::FollowKoi), look_down)
+            .add_systems(Update, (start_following, start_tour))
+            .add_systems(
+                Update,
+                (tour, follow)
+                    .chain()
+                    .run_if(in_state(CameraMode::FollowKoi))
+                    .before(PanOrbitCameraSystemSet),
+            );
+    }
+}
+
+/// The koi after `current` in order of pedigree id, wrapping round to the first.
+fn next_koi<'a>(current: Option<Entity>, koi_query: impl Iterator<Item = (Entity, &'a Koi)>) -> Option<(Entity, &'a Koi)> {
+    let mut koi: Vec<_> = koi_query.collect();
+    koi.sort_by_key(|(_, koi)| koi.id);
+
+    let after = current
+        .and_then(|current| koi.iter().position(|(entity, _)| *entity == current))
+        .map_or(0, |index| index + 1);
+
+    koi.get(after).or(koi.first()).copied()
+}
+
+/// Y follows the selected koi, or the one under the cursor.
+fn start_following(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut notifications: EventWriter<Notification>,
+    mut target: ResMut<FollowTarget>,
+    selected_koi: Res<SelectedKoi>,
+    hovered_koi: Res<HoveredKoi>,
+    koi_query: Query<&Koi>,
+    state: Res<State<CameraMode>>,
+    mut next_state: ResMut<NextState<CameraMode>>,
+) {
+    let following = *state.get() == CameraMode::FollowKoi;
+
+    if following && keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(CameraMode::Orbit);
+        return;
+    }
+
+    if !keyboard_input.just_pressed(KeyCode::KeyY) {
+        return;
+    }
+
+    if following && !target.tour {
+        next_state.set(CameraMode::Orbit);
+        return;
+    }
+
+    let Some((entity, koi)) = selected_koi.0.or(hovered_koi.0).and_then(|entity| koi_query.get(entity).ok().map(|koi| (entity, koi))) else {
+        notifications.send(Notification("Click on a koi to follow it".into()));
+        return;
+    };
+
+    target.koi = Some(entity);
+    target.tour = false;
+    next_state.set(CameraMode::FollowKoi);
+
+    notifications.send(Notification(format!("Following {} the {}, Y or Esc: stop", koi.name, koi.variety())));
+}
+
+/// Z starts a tour that follows each koi in turn, like a screensaver.
+fn start_tour(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut notifications: EventWriter<Notification>,
+    mut target: ResMut<FollowTarget>,
+    koi_query: Query<(Entity, &Koi)>,
+    state: Res<State<CameraMode>>,
+    mut next_state: ResMut<NextState<CameraMode>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyZ) {
+        return;
+    }
+
+    if *state.get() == CameraMode::FollowKoi && target.tour {
+        next_state.set(CameraMode::Orbit);
+        return;
+    }
+
+    let Some((entity, koi)) = next_koi(None, koi_query.iter()) else {
+        notifications.send(Notification("There are no koi to tour".into()));
+        return;
+    };
+
+    target.koi = Some(entity);
+    target.tour = true;
+    target.timer.reset();
+    next_state.set(CameraMode::FollowKoi);
+
+    notifications.send(Notification(format!("Touring the koi, starting with {}. Z or Esc: stop", koi.name)));
+}
+
+/// Runs on entering the mode, after leaving planning has put back the old orbit view.
+fn look_down(mut camera_query: Query<&mut PanOrbitCamera>, tile_settings: Res<TileSettings>) {
+    let mut camera = camera_query.single_mut();
+
+    camera.target_pitch = FOLLOW_PITCH;
+    camera.target_radius = tile_settings.tile_size * FOLLOW_DISTANCE;
+}
+
+/// Moves the tour on to the next koi when its time is up or the koi it was following is gone.
+fn tour(
+    time: Res<Time>,
+    mut notifications: EventWriter<Notification>,
+    mut target: ResMut<FollowTarget>,
+    koi_query: Query<(Entity, &Koi)>,
+) {
+    if !target.tour {
+        return;
+    }
+
+    let gone = target.koi.map_or(true, |entity| !koi_query.contains(entity));
+
+    if !target.timer.tick(time.delta()).just_finished() && !gone {
+        return;
+    }
+
+    if let Some((entity, koi)) = next_koi(target.koi, koi_query.iter()) {
+        if target.koi != Some(entity) {
+            notifications.send(Notification(format!("{} the {}", koi.name, koi.variety())));
+        }
+
+        target.koi = Some(entity);
+        target.timer.reset();
+    }
+}
+
+/// Keeps the orbit camera centred on the koi, its own smoothing keeps the motion steady while
+/// zooming and orbiting around the fish still work.
+fn follow(
+    mut notifications: EventWriter<Notification>,
+    target: Res<FollowTarget>,
+    koi_query: Query<&Transform, With<Koi>>,
+    mut next_state: ResMut<NextState<CameraMode>>,
+    mut camera_query: Query<&mut PanOrbitCamera>,
+) {
+    let Some(koi_transform) = target.koi.and_then(|entity| koi_query.get(entity).ok()) else {
+        notifications.send(Notification("The koi you were following is gone".into()));
+        next_state.set(CameraMode::Orbit);
+        return;
+    };
+
+    let mut camera = camera_query.single_mut();
+
+    // the fish swims below the surface, orbit around the spot on the surface above it so the
+    // camera never dips under the water
+    let focus = Vec3::new(koi_transform.translation.x, WATER_LEVEL, koi_transform.translation.z);
+
+    if camera.target_focus != focus {
+        camera.target_focus = focus;
+    }
+}
@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+use super::{hover::cast_cursor_ray, pond::Ponds, tile::{to_top_down, Tile, TileSettings, TileType}, tools::ToolModeState, water::WATER_LEVEL};
+
+const PELLETS_PER_CLICK: u32 = 6;
+const PELLET_RADIUS: f32 = 0.08;
+const PELLET_NUTRITION: f32 = 0.05;
+// how far from the click pellets are scattered
+const SCATTER_RADIUS: f32 = 0.75;
+const DRIFT_SPEED: f32 = 0.2;
+// seconds a pellet floats on the surface before it starts to sink
+const FLOAT_DURATION: f32 = 20.0;
+const SINK_SPEED: f32 = 0.25;
+// seconds a pellet lies on the pond floor before it rots away
+const ROT_DURATION: f32 = 30.0;
+
+#[derive(Component, Debug)]
+pub struct FoodPellet {
+    pub nutrition: f32,
+    drift: Vec2,
+    // pellets drift within the bounds of the tile they were dropped on
+    bounds: Rect,
+    age: f32,
+    rot_timer: f32,
+}
+
+#[derive(Resource)]
+pub struct PelletAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub struct FoodPlugin;
+
+impl Plugin for FoodPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup)
+            .add_systems(Update, (drop_food, update_pellets));
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(PelletAssets {
+        mesh: meshes.add(Sphere::new(PELLET_RADIUS)),
+        material: materials.add(Color::rgb_u8(156, 110, 60)),
+    });
+}
+
+fn drop_food(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    windows: Query<&Window>,
+    tile_query: Query<&Tile>,
+    tile_settings: Res<TileSettings>,
+    pellet_assets: Res<PelletAssets>,
+    state: Res<State<ToolModeState>>,
+) {
+    if *state.get() != ToolModeState::Feed || !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (camera, camera_transform) = camera_query.single();
+
+    let Some((entity, point)) = cast_cursor_ray(camera, camera_transform, windows.single(), &rapier_context) else {
+        return;
+    };
+
+    let Ok(tile) = tile_query.get(entity) else {
+        return;
+    };
+
+    if tile.tile_type != TileType::Water {
+        return;
+    }
+
+    scatter_food(&mut commands, &pellet_assets, &tile_settings, to_top_down(tile.position), to_top_down(point));
+}
+
+/// Scatters a handful of pellets around `point` on the water tile centered at `tile_center`.
+pub fn scatter_food(
+    commands: &mut Commands,
+    pellet_assets: &PelletAssets,
+    tile_settings: &TileSettings,
+    tile_center: Vec2,
+    point: Vec2,
+) {
+    let bounds = Rect::from_center_size(tile_center, Vec2::splat(tile_settings.tile_size));
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..PELLETS_PER_CLICK {
+        let offset = Vec2::new(
+            rng.gen_range(-SCATTER_RADIUS..SCATTER_RADIUS),
+            rng.gen_range(-SCATTER_RADIUS..SCATTER_RADIUS),
+        );
+        let position = (point + offset).clamp(bounds.min, bounds.max);
+        let drift = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU)) * DRIFT_SPEED;
+
+        commands.spawn((
+            PbrBundle {
+                mesh: pellet_assets.mesh.clone(),
+                material: pellet_assets.material.clone(),
+                transform: Transform::from_xyz(position.x, WATER_LEVEL, position.y),
+                ..default()
+            },
+            FoodPellet {
+                nutrition: PELLET_NUTRITION,
+                drift,
+                bounds,
+                age: 0.0,
+                rot_timer: 0.0,
+            },
+        ));
+    }
+}
+
+fn update_pellets(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ponds: ResMut<Ponds>,
+    tile_settings: Res<TileSettings>,
+    mut pellet_query: Query<(Entity, &mut FoodPellet, &mut Transform)>,
+) {
+    let delta = time.delta_seconds();
+
+    for (entity, mut pellet, mut transform) in pellet_query.iter_mut() {
+        pellet.age += delta;
+
+        if pellet.age < FLOAT_DURATION {
+            // floating pellets drift along the surface
+            let position = (to_top_down(transform.translation) + pellet.drift * delta)
+                .clamp(pellet.bounds.min, pellet.bounds.max);
+
+            transform.translation = Vec3::new(position.x, WATER_LEVEL, position.y);
+        } else if transform.translation.y > PELLET_RADIUS {
+            transform.translation.y = (transform.translation.y - SINK_SPEED * delta).max(PELLET_RADIUS);
+        } else {
+            pellet.rot_timer += delta;
+
+            // uneaten food rots on the pond floor and fouls the water
+            if pellet.rot_timer >= ROT_DURATION {
+                ponds.add_waste(tile_settings.grid_coords(to_top_down(transform.translation)), pellet.nutrition);
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
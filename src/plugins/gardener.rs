@@ -0,0 +1,581 @@
+use std::{cmp::Reverse, collections::{HashMap, VecDeque}, fmt};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{economy::Wallet, equipment::{Equipment, EquipmentType}, food::{scatter_food, PelletAssets}, koi::Koi, market::MarketView, notification::Notification, pathfinding::Pathfinder, plant::Plant, pond::{Ponds, NEIGHBOURS}, save::GardenLoaded, tile::{to_top_down, TileSettings}, visitor::GATE};
+
+// real seconds between looking around the garden for work
+const SCAN_INTERVAL: f32 = 5.0;
+// real seconds between paydays
+const WAGE_INTERVAL: f32 = 60.0;
+const WAGE: u32 = 50;
+const MAX_GARDENERS: usize = 6;
+const WALK_SPEED: f32 = 3.0;
+const ARRIVAL_DISTANCE: f32 = 0.1;
+// how far above the tile surface gardeners stand
+const GARDENER_HEIGHT: f32 = 0.8;
+const MAX_PRIORITY: u32 = 3;
+// conditions that need a gardener's attention
+const DIRTY_FILTER: f32 = 0.3;
+const HUNGRY_POND: f32 = 0.5;
+const MURKY_POND: f32 = 0.6;
+// what a gardener's work does
+const TRIMMED_GROWTH: f32 = 0.5;
+const ALGAE_CLEARED: f32 = 0.3;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum TaskKind {
+    CleanFilter,
+    FeedFish,
+    TrimPlant,
+    ClearAlgae,
+}
+
+impl TaskKind {
+    const ALL: [TaskKind; 4] = [TaskKind::CleanFilter, TaskKind::FeedFish, TaskKind::TrimPlant, TaskKind::ClearAlgae];
+
+    /// Whether the task is done for a whole pond rather than a single tile.
+    fn is_pond_work(&self) -> bool {
+        matches!(self, TaskKind::FeedFish | TaskKind::ClearAlgae)
+    }
+
+    /// Seconds of work the task takes once the gardener reaches it.
+    fn work_time(&self) -> f32 {
+        match self {
+            TaskKind::CleanFilter => 6.0,
+            TaskKind::FeedFish => 2.0,
+            TaskKind::TrimPlant => 4.0,
+            TaskKind::ClearAlgae => 8.0,
+        }
+    }
+}
+
+impl fmt::Display for TaskKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskKind::CleanFilter => write!(f, "clean filters"),
+            TaskKind::FeedFish => write!(f, "feed fish"),
+            TaskKind::TrimPlant => write!(f, "trim plants"),
+            TaskKind::ClearAlgae => write!(f, "clear algae"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Task {
+    pub kind: TaskKind,
+    /// Tile the work is done on, gardeners stand on it or next to it when it is water.
+    pub coords: IVec2,
+}
+
+impl Task {
+    /// Whether both tasks are the same job, pond work counts once per pond wherever on the bank
+    /// it is done from.
+    fn same_job(&self, other: &Task, ponds: &Ponds) -> bool {
+        if self.kind != other.kind {
+            return false;
+        }
+
+        if self.kind.is_pond_work() {
+            let pond_id = |task: &Task| ponds.pond_at(task.coords).map(|pond| pond.id);
+
+            if let (Some(a), Some(b)) = (pond_id(self), pond_id(other)) {
+                return a == b;
+            }
+        }
+
+        self.coords == other.coords
+    }
+}
+
+/// Work waiting for a gardener, and how urgently the player wants each kind of task done.
+#[derive(Resource, Debug)]
+pub struct TaskQueue {
+    pub tasks: Vec<Task>,
+    /// Priority of each kind of task, from 0 (never) to MAX_PRIORITY.
+    pub priorities: HashMap<TaskKind, u32>,
+}
+
+impl Default for TaskQueue {
+    fn default() -> Self {
+        Self {
+            tasks: Vec::new(),
+            priorities: TaskKind::ALL.into_iter().map(|kind| (kind, 1)).collect(),
+        }
+    }
+}
+
+impl TaskQueue {
+    pub fn priority(&self, kind: TaskKind) -> u32 {
+        self.priorities.get(&kind).copied().unwrap_or(1)
+    }
+
+    /// Takes the most urgent task, choosing the one nearest to `coords` out of equally urgent tasks.
+    pub fn take_next(&mut self, coords: IVec2) -> Option<Task> {
+        let (index, _) = self
+            .tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| self.priority(task.kind) > 0)
+            .min_by_key(|(_, task)| {
+                let distance = (task.coords - coords).abs();
+
+                (Reverse(self.priority(task.kind)), distance.x + distance.y)
+            })?;
+
+        Some(self.tasks.swap_remove(index))
+    }
+}
+
+#[derive(Component, Debug, Default)]
+pub struct Gardener {
+    task: Option<Task>,
+    route: VecDeque<IVec2>,
+    work: Option<Timer>,
+}
+
+/// Sent when a gardener finishes working on a task.
+#[derive(Event, Debug, Clone, Copy)]
+struct TaskDone(Task);
+
+#[derive(Resource)]
+struct StaffSchedule {
+    scan_timer: Timer,
+    wage_timer: Timer,
+}
+
+#[derive(Resource)]
+struct GardenerAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+/// The staff panel, it shares the arrow keys and enter with the market so only one of them is
+/// open at a time.
+#[derive(Component, Default)]
+pub struct StaffPanel {
+    chosen_task: usize,
+}
+
+pub struct GardenerPlugin;
+
+impl Plugin for GardenerPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<TaskDone>()
+            .insert_resource(TaskQueue::default())
+            .insert_resource(StaffSchedule {
+                scan_timer: Timer::from_seconds(SCAN_INTERVAL, TimerMode::Repeating),
+                wage_timer: Timer::from_seconds(WAGE_INTERVAL, TimerMode::Repeating),
+            })
+            .add_systems(Startup, setup)
+            .add_systems(Update, (
+                scan_for_tasks,
+                assign_tasks.after(scan_for_tasks),
+                work.after(assign_tasks),
+                complete_tasks.after(work),
+                pay_wages,
+                toggle_panel,
+                manage_staff,
+                update_panel.after(manage_staff),
+                load_gardeners,
+            ));
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(GardenerAssets {
+        mesh: meshes.add(Capsule3d::new(0.3, 1.0)),
+        material: materials.add(Color::rgb_u8(60, 110, 50)),
+    });
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(50.),
+            right: Val::Px(15.),
+            padding: UiRect::all(Val::Px(8.)),
+            display: Display::None,
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.8)),
+        StaffPanel::default(),
+    ));
+}
+
+/// Looks around the garden and replaces the queue with everything that needs doing, except
+/// tasks a gardener is already on.
+fn scan_for_tasks(
+    time: Res<Time>,
+    mut schedule: ResMut<StaffSchedule>,
+    mut queue: ResMut<TaskQueue>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+    equipment_query: Query<&Equipment>,
+    plant_query: Query<&Plant>,
+    koi_query: Query<(&Koi, &Transform)>,
+    gardener_query: Query<&Gardener>,
+) {
+    if !schedule.scan_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let mut tasks = Vec::new();
+
+    for equipment in equipment_query.iter() {
+        if equipment.equipment_type == EquipmentType::Filter && equipment.clogging >= DIRTY_FILTER {
+            tasks.push(Task { kind: TaskKind::CleanFilter, coords: equipment.coords });
+        }
+    }
+
+    for plant in plant_query.iter() {
+        // fully grown plants spread unless they are cut back
+        if plant.growth >= 1.0 {
+            tasks.push(Task { kind: TaskKind::TrimPlant, coords: plant.coords });
+        }
+    }
+
+    let mut hunger: HashMap<u32, (f32, u32)> = HashMap::new();
+
+    for (koi, transform) in koi_query.iter() {
+        if let Some(pond) = ponds.pond_at(tile_settings.grid_coords(to_top_down(transform.translation))) {
+            let (total, count) = hunger.entry(pond.id).or_default();
+            *total += koi.hunger;
+            *count += 1;
+        }
+    }
+
+    for pond in ponds.iter() {
+        // pond work is done from the edge so gardeners can reach it from the bank, always the
+        // same spot on it so rescans agree with the tasks already handed out
+        let Some(edge) = pond.tiles.iter().copied().filter(|coords| pond.is_shallow(*coords)).min_by_key(|coords| (coords.x, coords.y)) else {
+            continue;
+        };
+
+        if hunger.get(&pond.id).is_some_and(|(total, count)| total / *count as f32 >= HUNGRY_POND) {
+            tasks.push(Task { kind: TaskKind::FeedFish, coords: edge });
+        }
+
+        if pond.chemistry.clarity < MURKY_POND {
+            tasks.push(Task { kind: TaskKind::ClearAlgae, coords: edge });
+        }
+    }
+
+    let in_progress: Vec<Task> = gardener_query.iter().filter_map(|gardener| gardener.task).collect();
+
+    tasks.retain(|task| !in_progress.iter().any(|busy| busy.same_job(task, &ponds)));
+    queue.tasks = tasks;
+}
+
+/// Shortest route from `from` to a walkable tile on or next to `site`.
+fn route_to(pathfinder: &Pathfinder, from: IVec2, site: IVec2) -> Option<VecDeque<IVec2>> {
+    std::iter::once(site)
+        .chain(NEIGHBOURS.iter().map(|offset| site + *offset))
+        .filter_map(|coords| pathfinder.find_path(from, coords))
+        .min_by_key(|route| route.len())
+        .map(VecDeque::from)
+}
+
+/// Hands out work to idle gardeners right after each scan, routing is too costly to retry every
+/// frame so gardeners that finish early wait for the next scan.
+fn assign_tasks(
+    schedule: Res<StaffSchedule>,
+    mut queue: ResMut<TaskQueue>,
+    pathfinder: Res<Pathfinder>,
+    tile_settings: Res<TileSettings>,
+    mut gardener_query: Query<(&mut Gardener, &Transform)>,
+) {
+    if !schedule.scan_timer.just_finished() {
+        return;
+    }
+
+    for (mut gardener, transform) in gardener_query.iter_mut() {
+        if gardener.task.is_some() {
+            continue;
+        }
+
+        let coords = tile_settings.grid_coords(to_top_down(transform.translation));
+
+        // unreachable tasks are dropped until the next scan finds them again
+        while let Some(task) = queue.take_next(coords) {
+            if let Some(route) = route_to(&pathfinder, coords, task.coords) {
+                gardener.task = Some(task);
+                gardener.route = route;
+                break;
+            }
+        }
+    }
+}
+
+fn work(
+    mut task_done: EventWriter<TaskDone>,
+    time: Res<Time>,
+    pathfinder: Res<Pathfinder>,
+    tile_settings: Res<TileSettings>,
+    mut gardener_query: Query<(&mut Gardener, &mut Transform)>,
+) {
+    for (mut gardener, mut transform) in gardener_query.iter_mut() {
+        let Some(task) = gardener.task else {
+            continue;
+        };
+
+        if let Some(next) = gardener.route.front().copied() {
+            let position = tile_settings.world_position(next);
+            let height = pathfinder.height(next).unwrap_or(transform.translation.y) + GARDENER_HEIGHT;
+            let offset = Vec3::new(position.x, height, position.y) - transform.translation;
+
+            if offset.length() > ARRIVAL_DISTANCE {
+                transform.translation += offset.clamp_length_max(WALK_SPEED * time.delta_seconds());
+            } else {
+                gardener.route.pop_front();
+            }
+
+            continue;
+        }
+
+        let timer = gardener
+            .work
+            .get_or_insert_with(|| Timer::from_seconds(task.kind.work_time(), TimerMode::Once));
+
+        if timer.tick(time.delta()).finished() {
+            task_done.send(TaskDone(task));
+            gardener.task = None;
+            gardener.work = None;
+        }
+    }
+}
+
+fn complete_tasks(
+    mut commands: Commands,
+    mut task_done: EventReader<TaskDone>,
+    mut ponds: ResMut<Ponds>,
+    pellet_assets: Res<PelletAssets>,
+    tile_settings: Res<TileSettings>,
+    mut equipment_query: Query<&mut Equipment>,
+    mut plant_query: Query<&mut Plant>,
+) {
+    for TaskDone(task) in task_done.read() {
+        match task.kind {
+            TaskKind::CleanFilter => {
+                for mut equipment in equipment_query.iter_mut().filter(|equipment| equipment.coords == task.coords) {
+                    equipment.clogging = 0.0;
+                }
+            }
+            TaskKind::FeedFish => {
+                let position = tile_settings.world_position(task.coords);
+
+                scatter_food(&mut commands, &pellet_assets, &tile_settings, position, position);
+            }
+            TaskKind::TrimPlant => {
+                for mut plant in plant_query.iter_mut().filter(|plant| plant.coords == task.coords) {
+                    plant.growth = plant.growth.min(TRIMMED_GROWTH);
+                }
+            }
+            TaskKind::ClearAlgae => {
+                let Some(pond_id) = ponds.pond_at(task.coords).map(|pond| pond.id) else {
+                    continue;
+                };
+
+                if let Some(pond) = ponds.get_mut(pond_id) {
+                    pond.chemistry.clarity = (pond.chemistry.clarity + ALGAE_CLEARED).min(1.0);
+                }
+            }
+        }
+    }
+}
+
+fn pay_wages(
+    mut commands: Commands,
+    mut notifications: EventWriter<Notification>,
+    time: Res<Time>,
+    mut schedule: ResMut<StaffSchedule>,
+    mut wallet: ResMut<Wallet>,
+    gardener_query: Query<Entity, With<Gardener>>,
+) {
+    if !schedule.wage_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    for entity in gardener_query.iter() {
+        if wallet.spend(WAGE).is_err() {
+            notifications.send(Notification("A gardener quit because they could not be paid".into()));
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// H opens and closes the staff panel, opening it closes the market.
+fn toggle_panel(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut panel_query: Query<&mut Style, (With<StaffPanel>, Without<MarketView>)>,
+    mut market_query: Query<&mut Style, (With<MarketView>, Without<StaffPanel>)>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyH) {
+        let mut style = panel_query.single_mut();
+
+        style.display = match style.display {
+            Display::None => Display::Flex,
+            _ => Display::None,
+        };
+
+        if style.display != Display::None {
+            for mut market_style in market_query.iter_mut() {
+                market_style.display = Display::None;
+            }
+        }
+    }
+}
+
+/// While the staff panel is open Up/Down choose a kind of task and Left/Right change its
+/// priority, Enter hires a gardener and Backspace lets one go.
+fn manage_staff(
+    mut commands: Commands,
+    mut notifications: EventWriter<Notification>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut queue: ResMut<TaskQueue>,
+    gardener_assets: Res<GardenerAssets>,
+    pathfinder: Res<Pathfinder>,
+    tile_settings: Res<TileSettings>,
+    gardener_query: Query<(Entity, &Gardener)>,
+    mut panel_query: Query<(&mut StaffPanel, &Style)>,
+) {
+    let (mut panel, style) = panel_query.single_mut();
+
+    if style.display == Display::None {
+        return;
+    }
+
+    let kinds = TaskKind::ALL.len();
+
+    if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        panel.chosen_task = (panel.chosen_task + 1) % kinds;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        panel.chosen_task = (panel.chosen_task + kinds - 1) % kinds;
+    }
+
+    let kind = TaskKind::ALL[panel.chosen_task];
+    let priority = queue.priority(kind);
+
+    if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+        queue.priorities.insert(kind, (priority + 1).min(MAX_PRIORITY));
+    }
+
+    if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        queue.priorities.insert(kind, priority.saturating_sub(1));
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        if gardener_query.iter().count() >= MAX_GARDENERS {
+            notifications.send(Notification(format!("The garden can't use more than {} gardeners", MAX_GARDENERS)));
+            return;
+        }
+
+        let position = tile_settings.world_position(GATE);
+        let height = pathfinder.height(GATE).unwrap_or_default() + GARDENER_HEIGHT;
+
+        spawn_gardener(&mut commands, &gardener_assets, Vec3::new(position.x, height, position.y));
+
+        notifications.send(Notification(format!("Hired a gardener for ¥{} a minute", WAGE)));
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Backspace) {
+        // let an idle gardener go before one in the middle of a job
+        let Some((entity, _)) = gardener_query.iter().min_by_key(|(_, gardener)| gardener.task.is_some()) else {
+            return;
+        };
+
+        commands.entity(entity).despawn_recursive();
+        notifications.send(Notification("Let a gardener go".into()));
+    }
+}
+
+fn spawn_gardener(commands: &mut Commands, gardener_assets: &GardenerAssets, translation: Vec3) {
+    commands.spawn((
+        PbrBundle {
+            mesh: gardener_assets.mesh.clone(),
+            material: gardener_assets.material.clone(),
+            transform: Transform::from_translation(translation),
+            ..default()
+        },
+        Gardener::default(),
+    ));
+}
+
+/// Replaces the staff with the gardeners of the loaded garden, they pick up work again at the
+/// next scan.
+fn load_gardeners(
+    mut commands: Commands,
+    mut garden_loaded: EventReader<GardenLoaded>,
+    mut queue: ResMut<TaskQueue>,
+    gardener_assets: Res<GardenerAssets>,
+    gardener_query: Query<Entity, With<Gardener>>,
+) {
+    let Some(GardenLoaded(save)) = garden_loaded.read().last() else {
+        return;
+    };
+
+    for entity in gardener_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    for record in save.gardeners.iter() {
+        spawn_gardener(&mut commands, &gardener_assets, Vec3::from(record.position));
+    }
+
+    queue.tasks.clear();
+    queue.priorities = save.task_priorities.clone();
+}
+
+fn update_panel(
+    queue: Res<TaskQueue>,
+    gardener_query: Query<&Gardener>,
+    mut panel_query: Query<(&mut Text, &StaffPanel, &Style)>,
+) {
+    let (mut text, panel, style) = panel_query.single_mut();
+
+    if style.display == Display::None {
+        return;
+    }
+
+    let gardeners = gardener_query.iter().count();
+    let busy = gardener_query.iter().filter(|gardener| gardener.task.is_some()).count();
+
+    let priorities: Vec<String> = TaskKind::ALL
+        .iter()
+        .enumerate()
+        .map(|(index, kind)| {
+            let waiting = queue.tasks.iter().filter(|task| task.kind == *kind).count();
+
+            format!(
+                "{} {}: priority {}, {} waiting",
+                if index == panel.chosen_task { ">" } else { " " },
+                kind,
+                queue.priority(*kind),
+                waiting,
+            )
+        })
+        .collect();
+
+    text.sections[0].value = format!(
+        "Gardeners: {} ({} working), ¥{} each a minute\n{}\nUp/Down: choose  Left/Right: priority\nEnter: hire  Backspace: let go",
+        gardeners,
+        busy,
+        WAGE,
+        priorities.join("\n"),
+    );
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum BaseColor {
+    White,
+    Black,
+    Blue,
+    Brown,
+}
+
+/// Inherited traits of a koi, every trait other than the base color ranges from 0.0 to 1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Genome {
+    pub base_color: BaseColor,
+    pub metallic: bool,
+    /// Share of the body covered by red (hi) markings.
+    pub red_coverage: f32,
+    /// Share of the body covered by black (sumi) markings.
+    pub black_coverage: f32,
+    /// How vivid the colors are.
+    pub saturation: f32,
+    /// How evenly the markings are spread over both sides of the body.
+    pub pattern_balance: f32,
+    /// How close the body is to the ideal torpedo shape.
+    pub body_shape: f32,
+    /// How large the koi can grow.
+    pub growth_potential: f32,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum Variety {
+    Kohaku,
+    Sanke,
+    Showa,
+    ShiroMuji,
+    Karasu,
+    Asagi,
+    Chagoi,
+    Ogon,
+}
+
+impl fmt::Display for Variety {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Variety::Kohaku => "Kohaku",
+            Variety::Sanke => "Taisho Sanke",
+            Variety::Showa => "Showa Sanshoku",
+            Variety::ShiroMuji => "Shiro Muji",
+            Variety::Karasu => "Karasu",
+            Variety::Asagi => "Asagi",
+            Variety::Chagoi => "Chagoi",
+            Variety::Ogon => "Ogon",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+// coverage below this is too faint to count as a marking
+const MARKING_THRESHOLD: f32 = 0.1;
+// largest random change a trait can undergo when passed on to offspring
+const MUTATION: f32 = 0.1;
+const METALLIC_MUTATION_CHANCE: f64 = 0.02;
+
+impl Genome {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let base_color = match rng.gen_range(0..4) {
+            0 => BaseColor::White,
+            1 => BaseColor::Black,
+            2 => BaseColor::Blue,
+            _ => BaseColor::Brown,
+        };
+
+        Self {
+            base_color,
+            metallic: rng.gen_bool(0.1),
+            red_coverage: rng.gen(),
+            black_coverage: rng.gen_range(0.0..0.5),
+            saturation: rng.gen(),
+            pattern_balance: rng.gen(),
+            body_shape: rng.gen(),
+            growth_potential: rng.gen(),
+        }
+    }
+
+    /// Combines the genomes of two parents, each trait is inherited from one of them with a
+    /// small random mutation.
+    pub fn cross(&self, other: &Genome, rng: &mut impl Rng) -> Self {
+        let mut inherit = |a: f32, b: f32| {
+            let trait_value = if rng.gen_bool(0.5) { a } else { b };
+
+            (trait_value + rng.gen_range(-MUTATION..=MUTATION)).clamp(0.0, 1.0)
+        };
+
+        let red_coverage = inherit(self.red_coverage, other.red_coverage);
+        let black_coverage = inherit(self.black_coverage, other.black_coverage);
+        let saturation = inherit(self.saturation, other.saturation);
+        let pattern_balance = inherit(self.pattern_balance, other.pattern_balance);
+        let body_shape = inherit(self.body_shape, other.body_shape);
+        let growth_potential = inherit(self.growth_potential, other.growth_potential);
+
+        let metallic = match (self.metallic, other.metallic) {
+            (true, true) => true,
+            (true, false) | (false, true) => rng.gen_bool(0.5),
+            (false, false) => rng.gen_bool(METALLIC_MUTATION_CHANCE),
+        };
+
+        Self {
+            base_color: if rng.gen_bool(0.5) { self.base_color } else { other.base_color },
+            metallic,
+            red_coverage,
+            black_coverage,
+            saturation,
+            pattern_balance,
+            body_shape,
+            growth_potential,
+        }
+    }
+
+    pub fn variety(&self) -> Variety {
+        let red = self.red_coverage >= MARKING_THRESHOLD;
+        let black = self.black_coverage >= MARKING_THRESHOLD;
+
+        if self.metallic {
+            return Variety::Ogon;
+        }
+
+        match self.base_color {
+            BaseColor::White if red && black => Variety::Sanke,
+            BaseColor::White if red => Variety::Kohaku,
+            BaseColor::White => Variety::ShiroMuji,
+            BaseColor::Black if red => Variety::Showa,
+            BaseColor::Black => Variety::Karasu,
+            BaseColor::Blue => Variety::Asagi,
+            BaseColor::Brown => Variety::Chagoi,
+        }
+    }
+
+    /// Single color used to render the koi, the base color tinted by its markings.
+    pub fn display_color(&self) -> Color {
+        let base = match (self.metallic, self.base_color) {
+            (true, _) => Vec3::new(0.93, 0.76, 0.3),
+            (false, BaseColor::White) => Vec3::new(0.95, 0.95, 0.92),
+            (false, BaseColor::Black) => Vec3::new(0.1, 0.1, 0.12),
+            (false, BaseColor::Blue) => Vec3::new(0.45, 0.55, 0.7),
+            (false, BaseColor::Brown) => Vec3::new(0.5, 0.36, 0.22),
+        };
+        let red = Vec3::new(0.85, 0.2, 0.1);
+        let black = Vec3::new(0.05, 0.05, 0.05);
+
+        let color = base
+            .lerp(red, self.red_coverage * 0.6)
+            .lerp(black, self.black_coverage * 0.4);
+        // washed out koi fade towards grey
+        let grey = Vec3::splat(color.dot(Vec3::new(0.3, 0.59, 0.11)));
+
+        let color = grey.lerp(color, 0.4 + self.saturation * 0.6);
+
+        Color::rgb(color.x, color.y, color.z)
+    }
+}
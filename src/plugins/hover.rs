@@ -1,7 +1,11 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
-use super::tile::Tile;
+use super::{koi::Koi, tile::Tile, tools::ToolModeState};
+
+/// Collision group for colliders that only get in the way of walking, such as decorations.
+/// Cursor rays pass straight through them to the tiles underneath.
+pub const OBSTACLE_GROUP: Group = Group::GROUP_2;
 
 #[derive(Component)]
 pub struct Interactable;
@@ -9,12 +13,22 @@ pub struct Interactable;
 #[derive(Component)]
 struct Hovered;
 
+/// The koi under the cursor, if any.
+#[derive(Resource, Default, PartialEq)]
+pub struct HoveredKoi(pub Option<Entity>);
+
+/// The koi the player clicked on, if any.
+#[derive(Resource, Default)]
+pub struct SelectedKoi(pub Option<Entity>);
+
 pub struct HoverPlugin;
 
 impl Plugin for HoverPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(Update, (hover, handle_hover_enter, handle_hover_exit));
+            .insert_resource(HoveredKoi::default())
+            .insert_resource(SelectedKoi::default())
+            .add_systems(Update, (hover, handle_hover_enter, handle_hover_exit, hover_koi, select_koi.after(hover_koi)));
     }
 }
 
@@ -54,21 +68,7 @@ fn hover(
 ) {
     let (camera, camera_transform) = camera_query.single();
 
-    let Some(cursor_position) = windows.single().cursor_position() else {
-        return;
-    };
-
-    let Some(ray) = camera.viewport_to_world(camera_transform, cursor_position) else {
-        return;
-    };
-
-    if let Some((entity, _toi)) = rapier_context.cast_ray(
-        ray.origin,
-        ray.direction.into(),
-        f32::MAX,
-        true,
-        QueryFilter::default(),
-    ) {
+    if let Some((entity, _point)) = cast_cursor_ray(camera, camera_transform, windows.single(), &rapier_context) {
         match interactable_query.get(entity) {
             // handle hovering over entities
             Ok(_) => {
@@ -88,3 +88,80 @@ fn hover(
         }
     }
 }
+
+fn hover_koi(
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    windows: Query<&Window>,
+    rapier_context: Res<RapierContext>,
+    koi_query: Query<(), With<Koi>>,
+    mut hovered_koi: ResMut<HoveredKoi>,
+) {
+    let (camera, camera_transform) = camera_query.single();
+
+    let hovered = cursor_ray(camera, camera_transform, windows.single()).and_then(|ray| {
+        rapier_context.cast_ray(
+            ray.origin,
+            ray.direction.into(),
+            f32::MAX,
+            true,
+            QueryFilter::default().predicate(&|entity| koi_query.contains(entity)),
+        )
+    });
+
+    hovered_koi.set_if_neq(HoveredKoi(hovered.map(|(entity, _toi)| entity)));
+}
+
+fn select_koi(
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    state: Res<State<ToolModeState>>,
+    hovered_koi: Res<HoveredKoi>,
+    mut selected_koi: ResMut<SelectedKoi>,
+    koi_query: Query<(), With<Koi>>,
+) {
+    // clicks only select koi when no tool would act on them
+    if *state.get() == ToolModeState::None && mouse_button_input.just_pressed(MouseButton::Left) {
+        if let Some(entity) = hovered_koi.0 {
+            selected_koi.0 = Some(entity);
+        }
+    }
+
+    let despawned = selected_koi.0.is_some_and(|entity| !koi_query.contains(entity));
+
+    if keyboard_input.just_pressed(KeyCode::Escape) || despawned {
+        selected_koi.0 = None;
+    }
+}
+
+/// Returns the ray from the camera through the cursor, if the cursor is inside the window.
+///
+/// With a perspective camera the rays fan out from the eye, with the orthographic planning camera
+/// they run parallel, starting on its near plane so everything on screen can be hit.
+pub fn cursor_ray(camera: &Camera, camera_transform: &GlobalTransform, window: &Window) -> Option<Ray3d> {
+    let cursor_position = window.cursor_position()?;
+
+    camera.viewport_to_world(camera_transform, cursor_position)
+}
+
+/// Casts a ray from the cursor into the physics world and returns the first solid entity hit along
+/// with the world space point where the ray hit it. Sensors such as koi and obstacles are ignored.
+pub fn cast_cursor_ray(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    window: &Window,
+    rapier_context: &RapierContext,
+) -> Option<(Entity, Vec3)> {
+    let ray = cursor_ray(camera, camera_transform, window)?;
+
+    let (entity, toi) = rapier_context.cast_ray(
+        ray.origin,
+        ray.direction.into(),
+        f32::MAX,
+        true,
+        QueryFilter::default()
+            .exclude_sensors()
+            .groups(CollisionGroups::new(Group::ALL, !OBSTACLE_GROUP)),
+    )?;
+
+    Some((entity, ray.get_point(toi)))
+}
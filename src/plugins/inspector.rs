@@ -0,0 +1,170 @@
+use bevy::prelude::*;
+
+use super::{disease::Sick, genome::Genome, hover::{HoveredKoi, SelectedKoi}, koi::Koi, pedigree::Pedigree};
+
+// offset from the cursor or koi so the UI doesn't cover what it describes
+const TOOLTIP_OFFSET: Vec2 = Vec2::new(15.0, 15.0);
+const PANEL_OFFSET: Vec2 = Vec2::new(30.0, -30.0);
+
+#[derive(Component)]
+struct KoiTooltip;
+
+#[derive(Component)]
+struct KoiInspectorPanel;
+
+#[derive(Component)]
+struct KoiInspectorText;
+
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup)
+            .add_systems(Update, (update_tooltip, update_panel));
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.6)),
+        KoiTooltip,
+    ));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    padding: UiRect::all(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            KoiInspectorPanel,
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 18.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                KoiInspectorText,
+            ));
+        });
+}
+
+fn update_tooltip(
+    hovered_koi: Res<HoveredKoi>,
+    koi_query: Query<&Koi>,
+    windows: Query<&Window>,
+    mut tooltip_query: Query<(&mut Text, &mut Style, &mut Visibility), With<KoiTooltip>>,
+) {
+    let (mut text, mut style, mut visibility) = tooltip_query.single_mut();
+
+    let hovered = hovered_koi.0.and_then(|entity| koi_query.get(entity).ok());
+
+    let (Some(koi), Some(cursor_position)) = (hovered, windows.single().cursor_position()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    text.sections[0].value = format!("{} ({})", koi.name, koi.variety());
+    style.left = Val::Px(cursor_position.x + TOOLTIP_OFFSET.x);
+    style.top = Val::Px(cursor_position.y + TOOLTIP_OFFSET.y);
+    *visibility = Visibility::Visible;
+}
+
+fn update_panel(
+    selected_koi: Res<SelectedKoi>,
+    pedigree: Res<Pedigree>,
+    koi_query: Query<(&Koi, &GlobalTransform, Option<&Sick>)>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut panel_query: Query<(&mut Style, &mut Visibility), With<KoiInspectorPanel>>,
+    mut text_query: Query<&mut Text, With<KoiInspectorText>>,
+) {
+    let (mut style, mut visibility) = panel_query.single_mut();
+
+    let Some((koi, koi_transform, sick)) = selected_koi.0.and_then(|entity| koi_query.get(entity).ok()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    let (camera, camera_transform) = camera_query.single();
+
+    // follow the koi around the screen
+    let Some(screen_position) = camera.world_to_viewport(camera_transform, koi_transform.translation()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    style.left = Val::Px(screen_position.x + PANEL_OFFSET.x);
+    style.top = Val::Px(screen_position.y + PANEL_OFFSET.y);
+    *visibility = Visibility::Visible;
+
+    text_query.single_mut().sections[0].value = [
+        koi.name.clone(),
+        format!("Variety: {}", koi.variety()),
+        format!("Age: {:.0} days", koi.age),
+        format!("Size: {:.0} cm", koi.size),
+        format!("Health: {:.0}%", koi.health * 100.0),
+        describe_condition(sick),
+        format!("Hunger: {:.0}%", koi.hunger * 100.0),
+        describe_lineage(&pedigree, koi),
+        describe_genome(&koi.genome),
+    ]
+    .join("\n");
+}
+
+fn describe_genome(genome: &Genome) -> String {
+    format!(
+        "Genome:\n  Base: {:?}{}\n  Red: {:.0}%  Black: {:.0}%\n  Saturation: {:.0}%  Balance: {:.0}%\n  Body: {:.0}%  Growth: {:.0}%",
+        genome.base_color,
+        if genome.metallic { " (metallic)" } else { "" },
+        genome.red_coverage * 100.0,
+        genome.black_coverage * 100.0,
+        genome.saturation * 100.0,
+        genome.pattern_balance * 100.0,
+        genome.body_shape * 100.0,
+        genome.growth_potential * 100.0,
+    )
+}
+
+fn describe_lineage(pedigree: &Pedigree, koi: &Koi) -> String {
+    let parents: Vec<String> = pedigree
+        .parents(koi.id)
+        .map(|parent| format!("{} #{}", parent.name, parent.id))
+        .collect();
+
+    if parents.is_empty() {
+        format!("Lineage: #{}, not bred in this garden", koi.id)
+    } else {
+        format!("Lineage: #{}, child of {}", koi.id, parents.join(" and "))
+    }
+}
+
+fn describe_condition(sick: Option<&Sick>) -> String {
+    match sick {
+        Some(sick) if sick.medicated => format!("Condition: {} (medicated)", sick.illness),
+        Some(sick) => format!("Condition: {}", sick.illness),
+        None => "Condition: healthy".to_string(),
+    }
+}
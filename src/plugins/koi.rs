@@ -0,0 +1,380 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::{seq::{IteratorRandom, SliceRandom}, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::{disease::Sick, economy::{Wallet, KOI_PRICE}, food::FoodPellet, genome::{Genome, Variety}, hover::cast_cursor_ray, notification::Notification, pedigree::Pedigree, pond::Ponds, save::GardenLoaded, season::CurrentSeason, tile::{to_top_down, Tile, TileSettings, TileType}, tools::ToolModeState, water::WATER_LEVEL};
+
+// depth below the water surface koi swim at when not feeding
+const SWIM_DEPTH: f32 = 1.0;
+const SWIM_SPEED: f32 = 1.5;
+// hunger gained per second, koi are starving at 1.0
+const HUNGER_RATE: f32 = 0.005;
+const ATTRACTION_RADIUS: f32 = 12.0;
+const EAT_DISTANCE: f32 = 0.4;
+const WANDER_RADIUS: f32 = 10.0;
+const ARRIVAL_DISTANCE: f32 = 0.2;
+// a koi day passes every real minute
+const DAYS_PER_SECOND: f32 = 1.0 / 60.0;
+// lengths in centimetres
+const HATCHLING_SIZE: f32 = 5.0;
+// age in days koi are able to breed
+const ADULT_AGE: f32 = 300.0;
+const MIN_ADULT_SIZE: f32 = 30.0;
+const MAX_ADULT_SIZE: f32 = 90.0;
+const GROWTH_PER_DAY: f32 = 0.5;
+// health lost per day to each source of stress, and regained per day when none apply
+const STRESS_DAMAGE: f32 = 0.05;
+const RECOVERY_RATE: f32 = 0.02;
+const STARVING_HUNGER: f32 = 0.8;
+const LOW_OXYGEN: f32 = 4.0;
+const HIGH_AMMONIA: f32 = 0.5;
+
+const NAMES: &[&str] = &[
+    "Hana", "Sakura", "Taro", "Yuki", "Kumo", "Sora", "Kin", "Gin", "Momo", "Hoshi", "Ume", "Kaze", "Tama", "Koko", "Sumi",
+];
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Koi {
+    /// Id of the koi's record in the pedigree.
+    pub id: u32,
+    pub name: String,
+    pub genome: Genome,
+    /// Age in days.
+    pub age: f32,
+    /// Length in centimetres.
+    pub size: f32,
+    /// From 0.0 (dead) to 1.0 (perfect health).
+    pub health: f32,
+    /// From 0.0 (full) to 1.0 (starving).
+    pub hunger: f32,
+    #[serde(skip)]
+    target: Option<Vec3>,
+}
+
+impl Koi {
+    pub fn new(name: String, genome: Genome, age: f32) -> Self {
+        let mut koi = Self {
+            id: 0,
+            name,
+            genome,
+            age,
+            size: HATCHLING_SIZE,
+            health: 1.0,
+            hunger: 0.5,
+            target: None,
+        };
+
+        koi.size = koi.size_at(age);
+        koi
+    }
+
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Self::new(random_name(rng), Genome::random(rng), rng.gen_range(100.0..1000.0))
+    }
+
+    /// A newly hatched koi bred from two parents.
+    pub fn hatchling(first_parent: &Koi, second_parent: &Koi, rng: &mut impl Rng) -> Self {
+        Self::new(random_name(rng), first_parent.genome.cross(&second_parent.genome, rng), 0.0)
+    }
+
+    pub fn variety(&self) -> Variety {
+        self.genome.variety()
+    }
+
+    pub fn is_adult(&self) -> bool {
+        self.age >= ADULT_AGE
+    }
+
+    /// Length the koi will reach once fully grown.
+    pub fn adult_size(&self) -> f32 {
+        MIN_ADULT_SIZE + (MAX_ADULT_SIZE - MIN_ADULT_SIZE) * self.genome.growth_potential
+    }
+
+    fn size_at(&self, age: f32) -> f32 {
+        (HATCHLING_SIZE + age * GROWTH_PER_DAY).min(self.adult_size())
+    }
+
+    pub fn eat(&mut self, nutrition: f32) {
+        self.hunger = (self.hunger - nutrition).max(0.0);
+    }
+
+    /// Moves the koi straight to `position`, for example when it is netted into another pond.
+    pub fn relocate(&mut self, transform: &mut Transform, position: Vec2) {
+        transform.translation = Vec3::new(position.x, WATER_LEVEL - SWIM_DEPTH, position.y);
+        self.target = None;
+    }
+}
+
+fn random_name(rng: &mut impl Rng) -> String {
+    NAMES.choose(rng).copied().unwrap_or("Koi").to_string()
+}
+
+#[derive(Resource)]
+pub struct KoiAssets {
+    mesh: Handle<Mesh>,
+}
+
+pub struct KoiPlugin;
+
+impl Plugin for KoiPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup)
+            .add_systems(Update, (release_koi, feed, swim.after(feed), age_koi, die.after(age_koi), load_koi));
+    }
+}
+
+fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(KoiAssets {
+        mesh: meshes.add(Sphere::new(0.5)),
+    });
+}
+
+/// Spawns a koi swimming at `position`, which is snapped to swimming depth.
+pub fn spawn_koi(
+    commands: &mut Commands,
+    materials: &mut Assets<StandardMaterial>,
+    koi_assets: &KoiAssets,
+    koi: Koi,
+    position: Vec2,
+) -> Entity {
+    commands.spawn((
+        PbrBundle {
+            mesh: koi_assets.mesh.clone(),
+            material: materials.add(koi.genome.display_color()),
+            transform: Transform::from_xyz(position.x, WATER_LEVEL - SWIM_DEPTH, position.y)
+                .with_scale(body_scale(koi.size)),
+            ..default()
+        },
+        Collider::ball(0.5),
+        Sensor,
+        koi,
+    )).id()
+}
+
+fn body_scale(size: f32) -> Vec3 {
+    Vec3::new(0.3, 0.2, 0.8) * (0.5 + size / MAX_ADULT_SIZE)
+}
+
+fn release_koi(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    rapier_context: Res<RapierContext>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    windows: Query<&Window>,
+    tile_query: Query<&Tile>,
+    koi_assets: Res<KoiAssets>,
+    mut pedigree: ResMut<Pedigree>,
+    mut notifications: EventWriter<Notification>,
+    mut wallet: ResMut<Wallet>,
+    state: Res<State<ToolModeState>>,
+) {
+    if *state.get() != ToolModeState::Koi || !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (camera, camera_transform) = camera_query.single();
+
+    let Some((entity, _point)) = cast_cursor_ray(camera, camera_transform, windows.single(), &rapier_context) else {
+        return;
+    };
+
+    let Ok(tile) = tile_query.get(entity) else {
+        return;
+    };
+
+    if tile.tile_type != TileType::Water {
+        return;
+    }
+
+    if let Err(error) = wallet.spend(KOI_PRICE) {
+        notifications.send(Notification(error.to_string()));
+        return;
+    }
+
+    let mut koi = Koi::random(&mut rand::thread_rng());
+    pedigree.register(&mut koi, None);
+
+    spawn_koi(&mut commands, &mut materials, &koi_assets, koi, to_top_down(tile.position));
+}
+
+fn feed(
+    mut commands: Commands,
+    time: Res<Time>,
+    season: Res<CurrentSeason>,
+    mut koi_query: Query<(&mut Koi, &Transform)>,
+    pellet_query: Query<(Entity, &FoodPellet, &Transform), Without<Koi>>,
+) {
+    let mut eaten = Vec::new();
+
+    for (mut koi, transform) in koi_query.iter_mut() {
+        // koi slow down in the cold and barely eat through the winter
+        koi.hunger = (koi.hunger + HUNGER_RATE * season.0.appetite() * time.delta_seconds()).min(1.0);
+
+        let nearest = pellet_query
+            .iter()
+            .filter(|(entity, _, _)| !eaten.contains(entity))
+            .map(|(entity, pellet, pellet_transform)| {
+                (entity, pellet, pellet_transform.translation, pellet_transform.translation.distance(transform.translation))
+            })
+            .filter(|(_, _, _, distance)| *distance <= ATTRACTION_RADIUS)
+            .min_by(|a, b| a.3.total_cmp(&b.3));
+
+        match nearest {
+            Some((entity, pellet, _, distance)) if distance <= EAT_DISTANCE => {
+                koi.eat(pellet.nutrition);
+                koi.target = None;
+                eaten.push(entity);
+                commands.entity(entity).despawn();
+            }
+            Some((_, _, position, _)) => {
+                koi.target = Some(position);
+            }
+            None => {}
+        }
+    }
+}
+
+fn swim(
+    time: Res<Time>,
+    mut koi_query: Query<(&mut Koi, &mut Transform)>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for (mut koi, mut transform) in koi_query.iter_mut() {
+        let target = match koi.target {
+            Some(target) if target.distance(transform.translation) > ARRIVAL_DISTANCE => target,
+            _ => {
+                let position = to_top_down(transform.translation);
+
+                let Some(pond) = ponds.pond_at(tile_settings.grid_coords(position)) else {
+                    continue;
+                };
+
+                let open_water = || pond.tiles.iter().filter(|coords| !pond.is_iced(**coords));
+
+                // wander to a random tile nearby without leaving the pond, when the shallows ice
+                // over koi caught under the ice head for the nearest deep water
+                let nearby = open_water()
+                    .filter(|coords| tile_settings.world_position(**coords).distance(position) <= WANDER_RADIUS)
+                    .choose(&mut rng);
+
+                let Some(coords) = nearby.or_else(|| {
+                    open_water().min_by(|a, b| {
+                        let distance = |coords: &IVec2| tile_settings.world_position(*coords).distance(position);
+
+                        distance(a).total_cmp(&distance(b))
+                    })
+                }) else {
+                    continue;
+                };
+
+                let destination = tile_settings.world_position(*coords);
+                let target = Vec3::new(destination.x, WATER_LEVEL - SWIM_DEPTH, destination.y);
+                koi.target = Some(target);
+                target
+            }
+        };
+
+        let direction = (target - transform.translation).normalize_or_zero();
+
+        if direction == Vec3::ZERO {
+            continue;
+        }
+
+        transform.translation += direction * SWIM_SPEED * time.delta_seconds();
+
+        let heading = Vec3::new(direction.x, 0.0, direction.z);
+
+        if heading != Vec3::ZERO {
+            transform.look_to(heading, Vec3::Y);
+        }
+    }
+}
+
+fn age_koi(
+    time: Res<Time>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+    mut koi_query: Query<(&mut Koi, &mut Transform)>,
+) {
+    let days = time.delta_seconds() * DAYS_PER_SECOND;
+
+    for (mut koi, mut transform) in koi_query.iter_mut() {
+        koi.age += days;
+
+        // hungry koi stop growing
+        let growth = (koi.size_at(koi.age) - koi.size) * (1.0 - koi.hunger);
+        koi.size += growth.max(0.0);
+
+        let mut stress = 0.0;
+
+        if koi.hunger >= STARVING_HUNGER {
+            stress += STRESS_DAMAGE;
+        }
+
+        if let Some(pond) = ponds.pond_at(tile_settings.grid_coords(to_top_down(transform.translation))) {
+            if pond.chemistry.oxygen < LOW_OXYGEN {
+                stress += STRESS_DAMAGE;
+            }
+
+            if pond.chemistry.ammonia > HIGH_AMMONIA {
+                stress += STRESS_DAMAGE;
+            }
+
+            // under the ice, only koi with no deep water to retreat to stay there for long
+            if pond.is_iced(tile_settings.grid_coords(to_top_down(transform.translation))) {
+                stress += STRESS_DAMAGE;
+            }
+        }
+
+        let change = if stress > 0.0 { -stress } else { RECOVERY_RATE };
+        koi.health = (koi.health + change * days).clamp(0.0, 1.0);
+
+        transform.scale = body_scale(koi.size);
+    }
+}
+
+fn die(
+    mut commands: Commands,
+    mut notifications: EventWriter<Notification>,
+    koi_query: Query<(Entity, &Koi)>,
+) {
+    for (entity, koi) in koi_query.iter() {
+        if koi.health <= 0.0 {
+            notifications.send(Notification(format!("{} the {} has died", koi.name, koi.variety())));
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn load_koi(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut garden_loaded: EventReader<GardenLoaded>,
+    koi_assets: Res<KoiAssets>,
+    koi_query: Query<Entity, With<Koi>>,
+) {
+    let Some(GardenLoaded(save)) = garden_loaded.read().last() else {
+        return;
+    };
+
+    for entity in koi_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    for record in save.koi.iter() {
+        let entity = spawn_koi(&mut commands, &mut materials, &koi_assets, record.koi.clone(), Vec2::from(record.position));
+
+        if let Some(illness) = record.illness {
+            commands.entity(entity).insert(Sick {
+                illness,
+                medicated: record.medicated,
+            });
+        }
+    }
+}
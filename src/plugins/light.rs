@@ -1,30 +1,245 @@
+use std::f32::consts::PI;
+
 use bevy::prelude::*;
 
+use super::{notification::Notification, save::{GardenLoaded, TimeOfDayRecord}, season::{CurrentSeason, Season}, water::mix_colors, weather::Weather};
+
+// hours the sun comes up and goes down
+const SUNRISE: f32 = 6.0;
+const SUNSET: f32 = 18.0;
+// real seconds a whole day takes at normal speed
+const DAY_LENGTH: f32 = 600.0;
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 32.0;
+// the sun and moon cross the sky tilted to the south so they are never straight overhead
+const ORBIT_TILT: f32 = 0.4;
+// only the direction of the lights matters, this just keeps them well clear of the garden
+const LIGHT_DISTANCE: f32 = 50.0;
+// sun height the light fades in and out over around sunrise and sunset
+const TWILIGHT: f32 = 0.25;
+// lux at noon and under a full moon
+const SUN_ILLUMINANCE: f32 = 1000.0;
+const MOON_ILLUMINANCE: f32 = 60.0;
+const DAY_AMBIENT_BRIGHTNESS: f32 = 1000.0;
+const NIGHT_AMBIENT_BRIGHTNESS: f32 = 120.0;
+const LOW_SUN_COLOR: Color = Color::rgb(1.0, 0.55, 0.3);
+const HIGH_SUN_COLOR: Color = Color::rgb(1.0, 0.97, 0.92);
+const MOON_COLOR: Color = Color::rgb(0.6, 0.7, 1.0);
+const NIGHT_AMBIENT_COLOR: Color = Color::rgb(0.3, 0.35, 0.6);
+
+/// The garden clock that moves the sun and moon across the sky.
+#[derive(Resource, Debug)]
+pub struct TimeOfDay {
+    /// Days since the garden was started, the seasons follow along.
+    pub day: u32,
+    /// Hours since midnight, from 0.0 up to 24.0.
+    pub hour: f32,
+    /// How many times faster than normal the day goes by.
+    pub speed: f32,
+    pub paused: bool,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self {
+            day: 0,
+            hour: 8.0,
+            speed: 1.0,
+            paused: false,
+        }
+    }
+}
+
+impl TimeOfDay {
+    /// Angle of the sun around its path, 0.0 at sunrise and PI at sunset.
+    fn sun_angle(&self) -> f32 {
+        (self.hour - SUNRISE) / (SUNSET - SUNRISE) * PI
+    }
+
+    /// How high the sun is, from -1.0 at midnight through 0.0 at sunrise and sunset to 1.0 at noon.
+    pub fn sun_height(&self) -> f32 {
+        self.sun_angle().sin()
+    }
+
+    /// How much daylight there is, from 0.0 at night to 1.0 once the sun is well up.
+    pub fn daylight(&self) -> f32 {
+        ((self.sun_height() + TWILIGHT) / (2.0 * TWILIGHT)).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Component)]
+struct Sun;
+
+#[derive(Component)]
+struct Moon;
+
+#[derive(Component)]
+struct ClockDisplay;
+
 pub struct LightPlugin;
 
 impl Plugin for LightPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(Startup, setup);
+            .insert_resource(TimeOfDay::default())
+            .add_systems(Startup, setup)
+            .add_systems(Update, (control_time, advance_time, load_time, update_lights, display_clock).chain());
     }
 }
 
-fn setup(mut commands: Commands) {
+fn setup(mut commands: Commands, season: Res<CurrentSeason>) {
     // ambient light
     // NOTE: The ambient light is used to scale how bright the environment map is so with a bright
     // environment map, use an appropriate color and brightness to match
     commands.insert_resource(AmbientLight {
-        color: Color::rgb_u8(240, 228, 76),
-        brightness: 1000.0,
+        color: season.0.ambient_tone(),
+        brightness: DAY_AMBIENT_BRIGHTNESS,
     });
 
-    commands.spawn(DirectionalLightBundle {
-        directional_light: DirectionalLight {
-            illuminance: 1000.0,
+    // both lights are placed by update_lights as soon as the clock starts
+    commands.spawn((
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                illuminance: SUN_ILLUMINANCE,
+                ..default()
+            },
             ..default()
         },
-        transform: Transform::from_xyz(-5.0, 50.0, -2.0)
-            .looking_at(Vec3::ZERO, Vec3::Y),
-        ..default()
-    });
+        Sun,
+    ));
+
+    commands.spawn((
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                illuminance: 0.0,
+                color: MOON_COLOR,
+                ..default()
+            },
+            ..default()
+        },
+        Moon,
+    ));
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 30.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.),
+            left: Val::Percent(58.),
+            ..default()
+        }),
+        ClockDisplay,
+    ));
+}
+
+/// Comma slows the day down, period speeds it up and slash pauses it.
+fn control_time(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut notifications: EventWriter<Notification>,
+    mut time_of_day: ResMut<TimeOfDay>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Slash) {
+        time_of_day.paused = !time_of_day.paused;
+
+        let message = if time_of_day.paused { "Time of day paused" } else { "Time of day running" };
+        notifications.send(Notification(message.into()));
+    }
+
+    let speed = if keyboard_input.just_pressed(KeyCode::Comma) {
+        time_of_day.speed / 2.0
+    } else if keyboard_input.just_pressed(KeyCode::Period) {
+        time_of_day.speed * 2.0
+    } else {
+        return;
+    };
+
+    time_of_day.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+    notifications.send(Notification(format!("Days pass at {}x speed", time_of_day.speed)));
+}
+
+fn advance_time(time: Res<Time>, mut time_of_day: ResMut<TimeOfDay>) {
+    if time_of_day.paused {
+        return;
+    }
+
+    let hour = time_of_day.hour + time.delta_seconds() * time_of_day.speed * 24.0 / DAY_LENGTH;
+
+    if hour >= 24.0 {
+        time_of_day.day += 1;
+    }
+
+    time_of_day.hour = hour.rem_euclid(24.0);
+}
+
+fn load_time(mut garden_loaded: EventReader<GardenLoaded>, mut time_of_day: ResMut<TimeOfDay>) {
+    let Some(GardenLoaded(save)) = garden_loaded.read().last() else {
+        return;
+    };
+
+    // saves from before the day had a clock keep the current time
+    if let Some(TimeOfDayRecord { day, hour, speed, paused }) = save.time_of_day {
+        time_of_day.day = day;
+        time_of_day.hour = hour.rem_euclid(24.0);
+        time_of_day.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+        time_of_day.paused = paused;
+    }
+}
+
+/// Moves the sun and moon across the sky and hands over from one to the other around dusk and
+/// dawn, with the ambient light following along in the tone of the season. Clouds, rain and fog
+/// hold back some of the sunlight.
+fn update_lights(
+    time_of_day: Res<TimeOfDay>,
+    season: Res<CurrentSeason>,
+    weather: Res<Weather>,
+    mut ambient_light: ResMut<AmbientLight>,
+    mut sun_query: Query<(&mut DirectionalLight, &mut Transform), (With<Sun>, Without<Moon>)>,
+    mut moon_query: Query<(&mut DirectionalLight, &mut Transform), (With<Moon>, Without<Sun>)>,
+) {
+    if !time_of_day.is_changed() && !season.is_changed() && !weather.is_changed() {
+        return;
+    }
+
+    let angle = time_of_day.sun_angle();
+    let sun_direction = Vec3::new(angle.cos(), angle.sin(), ORBIT_TILT).normalize();
+    let daylight = time_of_day.daylight();
+
+    for (mut light, mut transform) in sun_query.iter_mut() {
+        *transform = Transform::from_translation(sun_direction * LIGHT_DISTANCE).looking_at(Vec3::ZERO, Vec3::Y);
+        light.illuminance = SUN_ILLUMINANCE * daylight * weather.sunlight();
+        // reddens towards the horizon
+        light.color = mix_colors(LOW_SUN_COLOR, HIGH_SUN_COLOR, time_of_day.sun_height().max(0.0).sqrt());
+    }
+
+    // the moon is up whenever the sun is down
+    for (mut light, mut transform) in moon_query.iter_mut() {
+        *transform = Transform::from_translation(-sun_direction * LIGHT_DISTANCE).looking_at(Vec3::ZERO, Vec3::Y);
+        light.illuminance = MOON_ILLUMINANCE * (1.0 - daylight);
+    }
+
+    ambient_light.color = mix_colors(NIGHT_AMBIENT_COLOR, season.0.ambient_tone(), daylight);
+    ambient_light.brightness = NIGHT_AMBIENT_BRIGHTNESS + (DAY_AMBIENT_BRIGHTNESS - NIGHT_AMBIENT_BRIGHTNESS) * daylight;
+}
+
+fn display_clock(time_of_day: Res<TimeOfDay>, mut text_query: Query<&mut Text, With<ClockDisplay>>) {
+    if !time_of_day.is_changed() {
+        return;
+    }
+
+    let mut text = text_query.single_mut();
+    let minutes = (time_of_day.hour * 60.0) as u32;
+    let date = format!("{} day {}", Season::on_day(time_of_day.day), time_of_day.day + 1);
+
+    text.sections[0].value = if time_of_day.paused {
+        format!("{} {:02}:{:02} paused", date, minutes / 60, minutes % 60)
+    } else {
+        format!("{} {:02}:{:02} {}x", date, minutes / 60, minutes % 60, time_of_day.speed)
+    };
 }
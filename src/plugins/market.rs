@@ -0,0 +1,309 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, seq::IteratorRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::{economy::{koi_value, Wallet}, gardener::StaffPanel, koi::{spawn_koi, Koi, KoiAssets}, notification::Notification, pedigree::Pedigree, pond::Ponds, save::GardenLoaded, show::judge, tile::TileSettings};
+
+// real seconds between the market restocking
+const RESTOCK_INTERVAL: f32 = 120.0;
+const STOCK_SIZE: usize = 6;
+// dealers ask more than a koi would fetch when sold on
+const MIN_MARKUP: f32 = 1.1;
+const MAX_MARKUP: f32 = 1.5;
+// restocks remembered in the price history
+const PRICE_HISTORY_LENGTH: usize = 10;
+const SHOWN_GARDEN_KOI: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Offer {
+    pub koi: Koi,
+    pub price: u32,
+}
+
+/// Koi for sale at the dealer, the stock for each restock is generated from the market seed so
+/// the same garden always sees the same offers.
+#[derive(Resource)]
+pub struct Market {
+    pub seed: u64,
+    /// Number of times the market has restocked.
+    pub restocks: u64,
+    pub stock: Vec<Offer>,
+    /// Average asking price of each restock, oldest first.
+    pub price_history: VecDeque<u32>,
+    timer: Timer,
+    chosen_offer: usize,
+    destination: Option<u32>,
+}
+
+impl Market {
+    pub fn new(seed: u64) -> Self {
+        let mut market = Self {
+            seed,
+            restocks: 0,
+            stock: Vec::new(),
+            price_history: VecDeque::new(),
+            timer: Timer::from_seconds(RESTOCK_INTERVAL, TimerMode::Repeating),
+            chosen_offer: 0,
+            destination: None,
+        };
+
+        market.restock();
+        market
+    }
+
+    /// Replaces the stock with the offers for the next restock.
+    pub fn restock(&mut self) {
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(self.restocks));
+
+        self.stock = (0..STOCK_SIZE)
+            .map(|_| {
+                let koi = Koi::random(&mut rng);
+                let markup = rng.gen_range(MIN_MARKUP..MAX_MARKUP);
+                let price = (koi_value(&koi).max(1) as f32 * markup).round() as u32;
+
+                Offer { koi, price }
+            })
+            .collect();
+
+        self.restocks += 1;
+        self.chosen_offer = 0;
+
+        let average = self.stock.iter().map(|offer| offer.price).sum::<u32>() / STOCK_SIZE as u32;
+
+        self.price_history.push_back(average);
+
+        if self.price_history.len() > PRICE_HISTORY_LENGTH {
+            self.price_history.pop_front();
+        }
+    }
+}
+
+/// The market panel, it shares the arrow keys and enter with the staff panel so only one of them
+/// is open at a time.
+#[derive(Component)]
+pub struct MarketView;
+
+pub struct MarketPlugin;
+
+impl Plugin for MarketPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Market::new(rand::random()))
+            .add_systems(Startup, setup)
+            .add_systems(Update, (restock, toggle_view, browse, update_view.after(browse), load_market));
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(50.),
+            left: Val::Percent(30.),
+            padding: UiRect::all(Val::Px(8.)),
+            display: Display::None,
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.8)),
+        MarketView,
+    ));
+}
+
+fn restock(time: Res<Time>, mut market: ResMut<Market>) {
+    if market.timer.tick(time.delta()).just_finished() {
+        market.restock();
+    }
+}
+
+/// Brings back the offers left and the price history the market had when the garden was saved.
+fn load_market(mut garden_loaded: EventReader<GardenLoaded>, mut market: ResMut<Market>) {
+    let Some(GardenLoaded(save)) = garden_loaded.read().last() else {
+        return;
+    };
+
+    market.seed = save.market_seed;
+    market.price_history = save.market_price_history.iter().copied().collect();
+
+    match &save.market_stock {
+        Some(stock) => {
+            market.restocks = save.market_restocks;
+            market.stock = stock.clone();
+            market.chosen_offer = 0;
+        }
+        // saves from before the stock was kept get the full stock of their last restock
+        None => {
+            market.restocks = save.market_restocks.saturating_sub(1);
+            market.restock();
+        }
+    }
+}
+
+/// B opens and closes the market, opening it closes the staff panel.
+fn toggle_view(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut view_query: Query<&mut Style, (With<MarketView>, Without<StaffPanel>)>,
+    mut staff_query: Query<&mut Style, (With<StaffPanel>, Without<MarketView>)>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyB) {
+        let mut style = view_query.single_mut();
+
+        style.display = match style.display {
+            Display::None => Display::Flex,
+            _ => Display::None,
+        };
+
+        if style.display != Display::None {
+            for mut staff_style in staff_query.iter_mut() {
+                staff_style.display = Display::None;
+            }
+        }
+    }
+}
+
+/// While the market is open the arrow keys choose an offer and the pond it is delivered to, and
+/// enter buys it.
+fn browse(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut notifications: EventWriter<Notification>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut market: ResMut<Market>,
+    mut wallet: ResMut<Wallet>,
+    mut pedigree: ResMut<Pedigree>,
+    koi_assets: Res<KoiAssets>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+    view_query: Query<&Style, With<MarketView>>,
+) {
+    if view_query.single().display == Display::None {
+        return;
+    }
+
+    let stock_size = market.stock.len();
+
+    if keyboard_input.just_pressed(KeyCode::ArrowDown) && stock_size > 0 {
+        market.chosen_offer = (market.chosen_offer + 1) % stock_size;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) && stock_size > 0 {
+        market.chosen_offer = (market.chosen_offer + stock_size - 1) % stock_size;
+    }
+
+    let pond_ids: Vec<u32> = ponds.iter().map(|pond| pond.id).collect();
+    let current = market.destination.and_then(|id| pond_ids.iter().position(|pond_id| *pond_id == id));
+
+    let destination = if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+        current.map_or(0, |index| index + 1) % pond_ids.len().max(1)
+    } else if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        current.map_or(0, |index| index + pond_ids.len().saturating_sub(1)) % pond_ids.len().max(1)
+    } else {
+        current.unwrap_or(0)
+    };
+
+    // ponds can be filled in or merged while the market is open
+    market.destination = pond_ids.get(destination).copied();
+
+    if !keyboard_input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+
+    let Some(pond) = market.destination.and_then(|id| ponds.get(id)) else {
+        notifications.send(Notification("Dig a pond before buying koi".into()));
+        return;
+    };
+
+    let Some(price) = market.stock.get(market.chosen_offer).map(|offer| offer.price) else {
+        notifications.send(Notification("The dealer has sold out until the next restock".into()));
+        return;
+    };
+
+    let Some(coords) = pond.tiles.iter().choose(&mut rand::thread_rng()) else {
+        return;
+    };
+
+    if let Err(error) = wallet.spend(price) {
+        notifications.send(Notification(error.to_string()));
+        return;
+    }
+
+    let chosen_offer = market.chosen_offer;
+    let Offer { mut koi, .. } = market.stock.remove(chosen_offer);
+    let remaining = market.stock.len();
+    market.chosen_offer = chosen_offer.min(remaining.saturating_sub(1));
+
+    pedigree.register(&mut koi, None);
+
+    notifications.send(Notification(format!(
+        "Bought {} the {} for ¥{}, delivered to pond {}",
+        koi.name,
+        koi.variety(),
+        price,
+        pond.id,
+    )));
+
+    spawn_koi(&mut commands, &mut materials, &koi_assets, koi, tile_settings.world_position(*coords));
+}
+
+fn update_view(
+    market: Res<Market>,
+    koi_query: Query<&Koi>,
+    mut view_query: Query<(&mut Text, &Style), With<MarketView>>,
+) {
+    let (mut text, style) = view_query.single_mut();
+
+    if style.display == Display::None {
+        return;
+    }
+
+    let offers: Vec<String> = market
+        .stock
+        .iter()
+        .enumerate()
+        .map(|(index, offer)| {
+            format!(
+                "{} {} ({}) score {:.0}, {:.0} cm  ¥{}",
+                if index == market.chosen_offer { ">" } else { " " },
+                offer.koi.name,
+                offer.koi.variety(),
+                judge(&offer.koi).total,
+                offer.koi.size,
+                offer.price,
+            )
+        })
+        .collect();
+
+    let mut garden_koi: Vec<(&Koi, u32)> = koi_query.iter().map(|koi| (koi, koi_value(koi))).collect();
+    garden_koi.sort_by(|a, b| b.1.cmp(&a.1));
+
+    let valuations: Vec<String> = garden_koi
+        .into_iter()
+        .take(SHOWN_GARDEN_KOI)
+        .map(|(koi, value)| format!("  {} ({}) ¥{}", koi.name, koi.variety(), value))
+        .collect();
+
+    let history: Vec<String> = market.price_history.iter().map(|price| format!("¥{}", price)).collect();
+
+    let destination = match market.destination {
+        Some(id) => format!("pond {}", id),
+        None => "no ponds".to_string(),
+    };
+
+    text.sections[0].value = format!(
+        "Koi market, restocks in {:.0}s\n{}\nDeliver to: {}\nUp/Down: choose  Left/Right: pond  Enter: buy\n\nYour most valuable koi (sell with X)\n{}\n\nAverage asking price: {}",
+        market.timer.remaining_secs(),
+        if offers.is_empty() { "  sold out".to_string() } else { offers.join("\n") },
+        destination,
+        if valuations.is_empty() { "  none".to_string() } else { valuations.join("\n") },
+        history.join(" "),
+    );
+}
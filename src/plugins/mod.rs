@@ -5,3 +5,30 @@ pub mod hover;
 pub mod tile;
 pub mod water;
 pub mod tools;
+pub mod food;
+pub mod koi;
+pub mod pond;
+pub mod water_quality;
+pub mod equipment;
+pub mod notification;
+pub mod plant;
+pub mod genome;
+pub mod inspector;
+pub mod breeding;
+pub mod pedigree;
+pub mod disease;
+pub mod predator;
+pub mod show;
+pub mod economy;
+pub mod market;
+pub mod visitor;
+pub mod pathfinding;
+pub mod gardener;
+pub mod decoration;
+pub mod save;
+pub mod first_person;
+pub mod follow_camera;
+pub mod camera_input;
+pub mod night_light;
+pub mod season;
+pub mod weather;
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+
+use super::{light::TimeOfDay, notification::Notification};
+
+// lights come on once the daylight has faded below this
+const DUSK_DAYLIGHT: f32 = 0.5;
+const DEFAULT_SHADOW_CASTERS: usize = 4;
+const MAX_SHADOW_CASTERS: usize = 16;
+
+/// A lantern or pond light that is switched on at dusk and off again at dawn.
+#[derive(Component)]
+pub struct NightLight;
+
+/// Shadows from point and spot lights are expensive, only the lights closest to the camera
+/// cast them.
+#[derive(Resource, Debug)]
+pub struct NightLighting {
+    pub max_shadow_casters: usize,
+}
+
+impl Default for NightLighting {
+    fn default() -> Self {
+        Self {
+            max_shadow_casters: DEFAULT_SHADOW_CASTERS,
+        }
+    }
+}
+
+pub struct NightLightPlugin;
+
+impl Plugin for NightLightPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(NightLighting::default())
+            .add_systems(Update, (change_shadow_limit, switch_lights, assign_shadows).chain());
+    }
+}
+
+/// [ and ] lower and raise the number of lights casting shadows.
+fn change_shadow_limit(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut notifications: EventWriter<Notification>,
+    mut lighting: ResMut<NightLighting>,
+) {
+    let limit = if keyboard_input.just_pressed(KeyCode::BracketLeft) {
+        lighting.max_shadow_casters.saturating_sub(1)
+    } else if keyboard_input.just_pressed(KeyCode::BracketRight) {
+        (lighting.max_shadow_casters + 1).min(MAX_SHADOW_CASTERS)
+    } else {
+        return;
+    };
+
+    lighting.max_shadow_casters = limit;
+    notifications.send(Notification(format!("Up to {} garden lights cast shadows", limit)));
+}
+
+fn switch_lights(time_of_day: Res<TimeOfDay>, mut light_query: Query<&mut Visibility, With<NightLight>>) {
+    let visibility = if time_of_day.daylight() < DUSK_DAYLIGHT { Visibility::Inherited } else { Visibility::Hidden };
+
+    for mut light_visibility in light_query.iter_mut() {
+        light_visibility.set_if_neq(visibility);
+    }
+}
+
+/// Gives shadows to the lit lights nearest the camera, up to the limit.
+fn assign_shadows(
+    lighting: Res<NightLighting>,
+    camera_query: Query<&GlobalTransform, With<Camera3d>>,
+    mut light_query: Query<(&GlobalTransform, &Visibility, Option<&mut PointLight>, Option<&mut SpotLight>), With<NightLight>>,
+) {
+    let Ok(camera_transform) = camera_query.get_single() else {
+        return;
+    };
+
+    let mut lights: Vec<_> = light_query.iter_mut().collect();
+
+    lights.sort_by(|(a, ..), (b, ..)| {
+        let distance = |transform: &GlobalTransform| transform.translation().distance_squared(camera_transform.translation());
+
+        distance(*a).total_cmp(&distance(*b))
+    });
+
+    let mut casters = 0;
+
+    for (_transform, visibility, point_light, spot_light) in lights {
+        let shadows = *visibility != Visibility::Hidden && casters < lighting.max_shadow_casters;
+
+        if shadows {
+            casters += 1;
+        }
+
+        // only write on a change so the lights aren't extracted again every frame
+        if let Some(mut light) = point_light {
+            if light.shadows_enabled != shadows {
+                light.shadows_enabled = shadows;
+            }
+        }
+
+        if let Some(mut light) = spot_light {
+            if light.shadows_enabled != shadows {
+                light.shadows_enabled = shadows;
+            }
+        }
+    }
+}
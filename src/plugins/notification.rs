@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+// seconds a notification stays on screen
+const NOTIFICATION_DURATION: f32 = 4.0;
+
+/// Shows a short message to the player at the bottom of the screen.
+#[derive(Event, Debug, Clone)]
+pub struct Notification(pub String);
+
+#[derive(Component)]
+struct NotificationDisplay {
+    timer: Timer,
+}
+
+pub struct NotificationPlugin;
+
+impl Plugin for NotificationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<Notification>()
+            .add_systems(Startup, setup)
+            .add_systems(Update, display_notifications);
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 24.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.),
+            left: Val::Px(15.),
+            ..default()
+        }),
+        NotificationDisplay {
+            timer: Timer::from_seconds(NOTIFICATION_DURATION, TimerMode::Once),
+        },
+    ));
+}
+
+fn display_notifications(
+    time: Res<Time>,
+    mut notifications: EventReader<Notification>,
+    mut display_query: Query<(&mut Text, &mut NotificationDisplay)>,
+) {
+    let (mut text, mut display) = display_query.single_mut();
+
+    if let Some(Notification(message)) = notifications.read().last() {
+        info!("{}", message);
+        text.sections[0].value = message.clone();
+        display.timer.reset();
+    } else if display.timer.tick(time.delta()).just_finished() {
+        text.sections[0].value.clear();
+    }
+}
@@ -0,0 +1,317 @@
+use std::{cmp::Ordering, collections::{BinaryHeap, HashMap}};
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::{hover::cast_cursor_ray, pond::NEIGHBOURS, tile::{to_top_down, Tile, TileSettings, TileType}, visitor::GATE};
+
+// extra cost per metre climbed or descended between neighbouring tiles
+const HEIGHT_COST: f32 = 2.0;
+// height above the tiles the debug path is drawn at
+const OVERLAY_HEIGHT: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy)]
+struct GridTile {
+    tile_type: TileType,
+    height: f32,
+}
+
+/// An entry in the A* open set, ordered so the binary heap pops the cheapest estimate first.
+#[derive(Debug, Clone, Copy)]
+struct Frontier {
+    estimate: f32,
+    coords: IVec2,
+}
+
+impl PartialEq for Frontier {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Frontier {}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Finds routes across the tile grid, kept in sync with the tiles as they are painted.
+#[derive(Resource, Debug, Default)]
+pub struct Pathfinder {
+    tiles: HashMap<IVec2, GridTile>,
+}
+
+impl Pathfinder {
+    /// Builds a pathfinder over a hand-made grid of (coords, tile type, height).
+    pub fn from_tiles(tiles: impl IntoIterator<Item = (IVec2, TileType, f32)>) -> Self {
+        let mut pathfinder = Self::default();
+
+        for (coords, tile_type, height) in tiles {
+            pathfinder.set_tile(coords, tile_type, height);
+        }
+
+        pathfinder
+    }
+
+    pub fn set_tile(&mut self, coords: IVec2, tile_type: TileType, height: f32) {
+        self.tiles.insert(coords, GridTile { tile_type, height });
+    }
+
+    pub fn tile_type(&self, coords: IVec2) -> Option<TileType> {
+        self.tiles.get(&coords).map(|tile| tile.tile_type)
+    }
+
+    pub fn height(&self, coords: IVec2) -> Option<f32> {
+        self.tiles.get(&coords).map(|tile| tile.height)
+    }
+
+    /// Cheapest route from `start` to `goal`, both included, over any walkable tiles.
+    pub fn find_path(&self, start: IVec2, goal: IVec2) -> Option<Vec<IVec2>> {
+        self.find_path_on(start, goal, |_| true)
+    }
+
+    /// Cheapest route from `start` to `goal`, both included, that only crosses tiles whose type
+    /// is `allowed`, for example to keep visitors on the paths.
+    pub fn find_path_on(&self, start: IVec2, goal: IVec2, allowed: impl Fn(TileType) -> bool) -> Option<Vec<IVec2>> {
+        let walkable = |coords: IVec2| {
+            self.tiles
+                .get(&coords)
+                .filter(|tile| allowed(tile.tile_type))
+                .and_then(|tile| tile.tile_type.walk_cost().map(|cost| (tile, cost)))
+        };
+
+        walkable(start)?;
+        walkable(goal)?;
+
+        // paths are the cheapest tiles so this never overestimates
+        let heuristic = |coords: IVec2| {
+            let distance = (goal - coords).abs();
+
+            (distance.x + distance.y) as f32 * TileType::Path.walk_cost().unwrap_or(1.0)
+        };
+
+        let mut open = BinaryHeap::from([Frontier { estimate: heuristic(start), coords: start }]);
+        let mut came_from: HashMap<IVec2, IVec2> = HashMap::new();
+        let mut costs: HashMap<IVec2, f32> = HashMap::from([(start, 0.0)]);
+
+        while let Some(Frontier { coords, estimate }) = open.pop() {
+            if coords == goal {
+                let mut path = vec![goal];
+                let mut current = goal;
+
+                while let Some(previous) = came_from.get(&current) {
+                    current = *previous;
+                    path.push(current);
+                }
+
+                path.reverse();
+                return Some(path);
+            }
+
+            let cost = costs[&coords];
+
+            // skip stale entries left behind when a cheaper route to the tile was found
+            if estimate > cost + heuristic(coords) {
+                continue;
+            }
+
+            let height = self.tiles[&coords].height;
+
+            for neighbour in NEIGHBOURS.iter().map(|offset| coords + *offset) {
+                let Some((tile, step_cost)) = walkable(neighbour) else {
+                    continue;
+                };
+
+                let neighbour_cost = cost + step_cost + (tile.height - height).abs() * HEIGHT_COST;
+
+                if costs.get(&neighbour).is_some_and(|known| *known <= neighbour_cost) {
+                    continue;
+                }
+
+                costs.insert(neighbour, neighbour_cost);
+                came_from.insert(neighbour, coords);
+                open.push(Frontier { estimate: neighbour_cost + heuristic(neighbour), coords: neighbour });
+            }
+        }
+
+        None
+    }
+}
+
+/// Draws the route from the garden gate to the tile under the cursor, toggled with F5.
+#[derive(Resource, Default)]
+struct PathOverlay {
+    enabled: bool,
+}
+
+pub struct PathfindingPlugin;
+
+impl Plugin for PathfindingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Pathfinder::default())
+            .insert_resource(PathOverlay::default())
+            .add_systems(Update, (sync_tiles, toggle_overlay, draw_overlay));
+    }
+}
+
+fn sync_tiles(
+    mut pathfinder: ResMut<Pathfinder>,
+    tile_settings: Res<TileSettings>,
+    tile_query: Query<&Tile, Changed<Tile>>,
+) {
+    for tile in tile_query.iter() {
+        pathfinder.set_tile(tile_settings.grid_coords(to_top_down(tile.position)), tile.tile_type, tile.height);
+    }
+}
+
+fn toggle_overlay(keyboard_input: Res<ButtonInput<KeyCode>>, mut overlay: ResMut<PathOverlay>) {
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        overlay.enabled = !overlay.enabled;
+    }
+}
+
+fn draw_overlay(
+    mut gizmos: Gizmos,
+    overlay: Res<PathOverlay>,
+    pathfinder: Res<Pathfinder>,
+    rapier_context: Res<RapierContext>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    windows: Query<&Window>,
+    tile_query: Query<&Tile>,
+    tile_settings: Res<TileSettings>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+
+    let (camera, camera_transform) = camera_query.single();
+
+    let Some(tile) = cast_cursor_ray(camera, camera_transform, windows.single(), &rapier_context)
+        .and_then(|(entity, _point)| tile_query.get(entity).ok()) else {
+        return;
+    };
+
+    let Some(path) = pathfinder.find_path(GATE, tile_settings.grid_coords(to_top_down(tile.position))) else {
+        return;
+    };
+
+    let points = path.iter().filter_map(|coords| {
+        let height = pathfinder.height(*coords)?;
+        let position = tile_settings.world_position(*coords);
+
+        Some(Vec3::new(position.x, height + OVERLAY_HEIGHT, position.y))
+    });
+
+    gizmos.linestrip(points, Color::FUCHSIA);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A flat grid built from rows of characters, `p` path, `g` grass, `d` dirt and `w` water.
+    fn grid(rows: &[&str]) -> Pathfinder {
+        Pathfinder::from_tiles(rows.iter().enumerate().flat_map(|(y, row)| {
+            row.chars().enumerate().map(move |(x, tile)| {
+                let tile_type = match tile {
+                    'p' => TileType::Path,
+                    'g' => TileType::Grass,
+                    'd' => TileType::Dirt,
+                    _ => TileType::Water,
+                };
+
+                (IVec2::new(x as i32, y as i32), tile_type, 5.0)
+            })
+        }))
+    }
+
+    #[test]
+    fn prefers_paths_over_shorter_grass() {
+        let pathfinder = grid(&[
+            "ggggg",
+            "ppppp",
+        ]);
+
+        let path = pathfinder.find_path(IVec2::new(0, 0), IVec2::new(4, 0)).unwrap();
+
+        // five tiles straight across the grass costs more than dropping onto the path
+        assert_eq!(path.len(), 7);
+        assert!(path.contains(&IVec2::new(2, 1)));
+    }
+
+    #[test]
+    fn water_blocks_the_way() {
+        let pathfinder = grid(&[
+            "ggwgg",
+            "ggwgg",
+            "ggwgg",
+        ]);
+
+        assert_eq!(pathfinder.find_path(IVec2::new(0, 1), IVec2::new(4, 1)), None);
+        assert_eq!(pathfinder.find_path(IVec2::new(0, 1), IVec2::new(2, 1)), None);
+    }
+
+    #[test]
+    fn enclosed_goal_is_unreachable() {
+        let pathfinder = grid(&[
+            "ggggg",
+            "ggwgg",
+            "gwgwg",
+            "ggwgg",
+        ]);
+
+        assert_eq!(pathfinder.find_path(IVec2::new(0, 0), IVec2::new(2, 2)), None);
+        assert!(pathfinder.find_path(IVec2::new(0, 0), IVec2::new(4, 3)).is_some());
+    }
+
+    #[test]
+    fn climbing_adds_cost() {
+        let mut pathfinder = grid(&[
+            "ggg",
+            "ggg",
+        ]);
+
+        let start = IVec2::new(0, 1);
+        let goal = IVec2::new(2, 1);
+        let hill = IVec2::new(1, 1);
+
+        assert_eq!(pathfinder.find_path(start, goal), Some(vec![start, hill, goal]));
+
+        pathfinder.set_tile(hill, TileType::Grass, 10.0);
+
+        let path = pathfinder.find_path(start, goal).unwrap();
+
+        assert!(!path.contains(&hill));
+        assert_eq!(path.len(), 5);
+    }
+
+    #[test]
+    fn keeps_to_allowed_tiles() {
+        let mut pathfinder = grid(&[
+            "ggggg",
+            "ppgpp",
+        ]);
+
+        let start = IVec2::new(0, 1);
+        let goal = IVec2::new(4, 1);
+        let only_paths = |tile_type: TileType| tile_type == TileType::Path;
+
+        assert!(pathfinder.find_path(start, goal).is_some());
+        assert_eq!(pathfinder.find_path_on(start, goal, only_paths), None);
+
+        pathfinder.set_tile(IVec2::new(2, 1), TileType::Path, 5.0);
+
+        let path = pathfinder.find_path_on(start, goal, only_paths).unwrap();
+
+        assert!(path.iter().all(|coords| pathfinder.tile_type(*coords) == Some(TileType::Path)));
+    }
+}
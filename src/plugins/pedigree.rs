@@ -0,0 +1,223 @@
+use std::{collections::BTreeMap, fs};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{genome::{Genome, Variety}, hover::SelectedKoi, koi::Koi, notification::Notification, save::GardenLoaded};
+
+// generations shown either side of the selected koi
+const VIEW_GENERATIONS: u32 = 3;
+const DOT_EXPORT_PATH: &str = "pedigree.dot";
+const JSON_EXPORT_PATH: &str = "pedigree.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PedigreeRecord {
+    pub id: u32,
+    pub name: String,
+    pub variety: Variety,
+    pub genome: Genome,
+    /// Ids of both parents, koi that were not bred in the garden have none.
+    pub parents: Option<[u32; 2]>,
+}
+
+/// Lineage of every koi that has lived in the garden, kept after the koi themselves are gone.
+#[derive(Resource, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Pedigree {
+    records: BTreeMap<u32, PedigreeRecord>,
+    next_id: u32,
+}
+
+impl Pedigree {
+    /// Gives the koi a new id and records its parentage.
+    pub fn register(&mut self, koi: &mut Koi, parents: Option<[u32; 2]>) {
+        self.next_id += 1;
+        koi.id = self.next_id;
+
+        self.records.insert(koi.id, PedigreeRecord {
+            id: koi.id,
+            name: koi.name.clone(),
+            variety: koi.variety(),
+            genome: koi.genome,
+            parents,
+        });
+    }
+
+    pub fn get(&self, id: u32) -> Option<&PedigreeRecord> {
+        self.records.get(&id)
+    }
+
+    pub fn parents(&self, id: u32) -> impl Iterator<Item = &PedigreeRecord> {
+        self.get(id)
+            .and_then(|record| record.parents)
+            .into_iter()
+            .flatten()
+            .filter_map(|parent| self.get(parent))
+    }
+
+    pub fn children(&self, id: u32) -> impl Iterator<Item = &PedigreeRecord> {
+        self.records
+            .values()
+            .filter(move |record| record.parents.is_some_and(|parents| parents.contains(&id)))
+    }
+
+    /// Ancestors up to `generations` back, paired with how many generations back they are.
+    pub fn ancestors(&self, id: u32, generations: u32) -> Vec<(u32, &PedigreeRecord)> {
+        self.walk(id, generations, |pedigree, id| pedigree.parents(id).collect())
+    }
+
+    /// Descendants up to `generations` down, paired with how many generations down they are.
+    pub fn descendants(&self, id: u32, generations: u32) -> Vec<(u32, &PedigreeRecord)> {
+        self.walk(id, generations, |pedigree, id| pedigree.children(id).collect())
+    }
+
+    fn walk<'a>(
+        &'a self,
+        id: u32,
+        generations: u32,
+        next: impl Fn(&'a Self, u32) -> Vec<&'a PedigreeRecord>,
+    ) -> Vec<(u32, &'a PedigreeRecord)> {
+        let mut found = Vec::new();
+        let mut current = vec![id];
+
+        for generation in 1..=generations {
+            let relatives: Vec<&PedigreeRecord> = current.iter().flat_map(|id| next(self, *id)).collect();
+
+            current = relatives.iter().map(|record| record.id).collect();
+            found.extend(relatives.into_iter().map(|record| (generation, record)));
+        }
+
+        found
+    }
+
+    /// Renders the whole pedigree as a Graphviz digraph with edges from parents to children.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph pedigree {\n");
+
+        for record in self.records.values() {
+            dot.push_str(&format!("    koi{} [label=\"{} #{}\\n{}\"];\n", record.id, record.name, record.id, record.variety));
+        }
+
+        for record in self.records.values() {
+            for parent in record.parents.into_iter().flatten() {
+                dot.push_str(&format!("    koi{} -> koi{};\n", parent, record.id));
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.records.values().collect::<Vec<_>>())
+    }
+}
+
+#[derive(Component)]
+struct PedigreeView;
+
+pub struct PedigreePlugin;
+
+impl Plugin for PedigreePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Pedigree::default())
+            .add_systems(Startup, setup)
+            .add_systems(Update, (toggle_view, update_view, export, load_pedigree));
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(50.),
+            right: Val::Px(15.),
+            padding: UiRect::all(Val::Px(8.)),
+            display: Display::None,
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.7)),
+        PedigreeView,
+    ));
+}
+
+fn toggle_view(keyboard_input: Res<ButtonInput<KeyCode>>, mut view_query: Query<&mut Style, With<PedigreeView>>) {
+    if keyboard_input.just_pressed(KeyCode::KeyT) {
+        let mut style = view_query.single_mut();
+
+        style.display = match style.display {
+            Display::None => Display::Flex,
+            _ => Display::None,
+        };
+    }
+}
+
+fn update_view(
+    pedigree: Res<Pedigree>,
+    selected_koi: Res<SelectedKoi>,
+    koi_query: Query<&Koi>,
+    mut view_query: Query<(&mut Text, &Style), With<PedigreeView>>,
+) {
+    let (mut text, style) = view_query.single_mut();
+
+    if style.display == Display::None {
+        return;
+    }
+
+    let Some(koi) = selected_koi.0.and_then(|entity| koi_query.get(entity).ok()) else {
+        text.sections[0].value = "Pedigree: select a koi".into();
+        return;
+    };
+
+    let describe = |(generation, record): (u32, &PedigreeRecord)| {
+        format!("{}{} #{} ({})", "  ".repeat(generation as usize), record.name, record.id, record.variety)
+    };
+
+    let ancestors: Vec<String> = pedigree.ancestors(koi.id, VIEW_GENERATIONS).into_iter().map(describe).collect();
+    let descendants: Vec<String> = pedigree.descendants(koi.id, VIEW_GENERATIONS).into_iter().map(describe).collect();
+
+    text.sections[0].value = format!(
+        "Pedigree of {} #{}\nAncestors:\n{}\nDescendants:\n{}\nF2: export DOT  F3: export JSON",
+        koi.name,
+        koi.id,
+        if ancestors.is_empty() { "  none".to_string() } else { ancestors.join("\n") },
+        if descendants.is_empty() { "  none".to_string() } else { descendants.join("\n") },
+    );
+}
+
+fn export(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    pedigree: Res<Pedigree>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let (path, contents) = if keyboard_input.just_pressed(KeyCode::F2) {
+        (DOT_EXPORT_PATH, Ok(pedigree.to_dot()))
+    } else if keyboard_input.just_pressed(KeyCode::F3) {
+        (JSON_EXPORT_PATH, pedigree.to_json().map_err(|error| error.to_string()))
+    } else {
+        return;
+    };
+
+    let result = contents.and_then(|contents| fs::write(path, contents).map_err(|error| error.to_string()));
+
+    let message = match result {
+        Ok(()) => format!("Exported pedigree to {}", path),
+        Err(error) => format!("Could not export pedigree to {}: {}", path, error),
+    };
+
+    notifications.send(Notification(message));
+}
+
+fn load_pedigree(mut garden_loaded: EventReader<GardenLoaded>, mut pedigree: ResMut<Pedigree>) {
+    if let Some(GardenLoaded(save)) = garden_loaded.read().last() {
+        *pedigree = save.pedigree.clone();
+    }
+}
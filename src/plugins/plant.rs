@@ -0,0 +1,307 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::{economy::Wallet, hover::cast_cursor_ray, notification::Notification, pond::{Ponds, NEIGHBOURS}, save::GardenLoaded, season::CurrentSeason, tile::{load_tiles, to_top_down, Tile, TileSettings}, tools::ToolModeState, water::WATER_LEVEL};
+
+// real seconds between growth ticks
+const GROWTH_TICK: f32 = 5.0;
+const GROWTH_PER_TICK: f32 = 0.05;
+// chance a fully grown plant seeds a neighbouring tile each tick
+const SPREAD_CHANCE: f64 = 0.05;
+const SEEDLING_GROWTH: f32 = 0.2;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum PlantSpecies {
+    LilyPad,
+    Lotus,
+    Reed,
+}
+
+impl PlantSpecies {
+    /// Picks the species that grows on the tile at `coords`: lily pads in open water, lotus in the
+    /// shallows at the edge of a pond and reeds on the bank. Narrow ponds are nothing but shallows,
+    /// so they get lily pads rather than leaving no room for them.
+    pub fn for_tile(coords: IVec2, ponds: &Ponds) -> Option<Self> {
+        match ponds.pond_at(coords) {
+            Some(pond) if pond.is_shallow(coords) && pond.has_deep_water() => Some(PlantSpecies::Lotus),
+            Some(_) => Some(PlantSpecies::LilyPad),
+            None => ponds.pond_touching(coords).map(|_| PlantSpecies::Reed),
+        }
+    }
+
+    pub fn can_grow(&self, coords: IVec2, ponds: &Ponds) -> bool {
+        match self {
+            PlantSpecies::LilyPad => ponds.pond_at(coords).is_some(),
+            PlantSpecies::Lotus => ponds.pond_at(coords).is_some_and(|pond| pond.is_shallow(coords)),
+            PlantSpecies::Reed => ponds.pond_at(coords).is_none() && ponds.pond_touching(coords).is_some(),
+        }
+    }
+
+    /// Share of a tile's water surface a fully grown plant shades.
+    pub fn cover(&self) -> f32 {
+        match self {
+            PlantSpecies::LilyPad => 0.6,
+            PlantSpecies::Lotus => 0.4,
+            PlantSpecies::Reed => 0.15,
+        }
+    }
+
+    pub fn price(&self) -> u32 {
+        match self {
+            PlantSpecies::LilyPad => 40,
+            PlantSpecies::Lotus => 80,
+            PlantSpecies::Reed => 20,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PlantSpecies::LilyPad => Color::rgb_u8(74, 140, 62),
+            PlantSpecies::Lotus => Color::rgb_u8(232, 150, 180),
+            PlantSpecies::Reed => Color::rgb_u8(140, 150, 84),
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Plant {
+    pub species: PlantSpecies,
+    pub coords: IVec2,
+    /// How grown the plant is, from 0.0 to 1.0.
+    pub growth: f32,
+}
+
+#[derive(Resource)]
+struct PlantAssets {
+    lily_pad_mesh: Handle<Mesh>,
+    lotus_mesh: Handle<Mesh>,
+    reed_mesh: Handle<Mesh>,
+}
+
+#[derive(Resource)]
+struct PlantGrowth {
+    timer: Timer,
+}
+
+pub struct PlantPlugin;
+
+impl Plugin for PlantPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(PlantGrowth {
+                timer: Timer::from_seconds(GROWTH_TICK, TimerMode::Repeating),
+            })
+            .add_systems(Startup, setup)
+            .add_systems(Update, (handle_plant_tool, grow_plants, scale_plants, load_plants.after(load_tiles)));
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    tile_settings: Res<TileSettings>,
+) {
+    let size = tile_settings.tile_size;
+
+    commands.insert_resource(PlantAssets {
+        lily_pad_mesh: meshes.add(Cylinder::new(size * 0.3, 0.05)),
+        lotus_mesh: meshes.add(Sphere::new(size * 0.15)),
+        reed_mesh: meshes.add(Cuboid::new(size * 0.2, size * 0.5, size * 0.2)),
+    });
+}
+
+fn spawn_plant(
+    commands: &mut Commands,
+    materials: &mut Assets<StandardMaterial>,
+    plant_assets: &PlantAssets,
+    tile_settings: &TileSettings,
+    tile: &Tile,
+    plant: Plant,
+) {
+    let position = tile_settings.world_position(plant.coords);
+
+    let (mesh, height) = match plant.species {
+        PlantSpecies::LilyPad => (plant_assets.lily_pad_mesh.clone(), WATER_LEVEL),
+        PlantSpecies::Lotus => (plant_assets.lotus_mesh.clone(), WATER_LEVEL),
+        // reeds stand on the bank
+        PlantSpecies::Reed => (plant_assets.reed_mesh.clone(), tile.height),
+    };
+
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material: materials.add(plant.species.color()),
+            transform: Transform::from_xyz(position.x, height, position.y),
+            ..default()
+        },
+        plant,
+    ));
+}
+
+fn handle_plant_tool(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut notifications: EventWriter<Notification>,
+    mut wallet: ResMut<Wallet>,
+    rapier_context: Res<RapierContext>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    windows: Query<&Window>,
+    tile_query: Query<&Tile>,
+    plant_query: Query<(Entity, &Plant)>,
+    plant_assets: Res<PlantAssets>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+    state: Res<State<ToolModeState>>,
+) {
+    if *state.get() != ToolModeState::Plant || !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (camera, camera_transform) = camera_query.single();
+
+    let Some((entity, _point)) = cast_cursor_ray(camera, camera_transform, windows.single(), &rapier_context) else {
+        return;
+    };
+
+    let Ok(tile) = tile_query.get(entity) else {
+        return;
+    };
+
+    let coords = tile_settings.grid_coords(to_top_down(tile.position));
+
+    // clicking a planted tile clears it
+    if let Some((plant_entity, _)) = plant_query.iter().find(|(_, plant)| plant.coords == coords) {
+        commands.entity(plant_entity).despawn();
+        return;
+    }
+
+    let Some(species) = PlantSpecies::for_tile(coords, &ponds) else {
+        notifications.send(Notification("Plants must be placed in a pond or on a tile next to one".into()));
+        return;
+    };
+
+    if let Err(error) = wallet.spend(species.price()) {
+        notifications.send(Notification(error.to_string()));
+        return;
+    }
+
+    let plant = Plant {
+        species,
+        coords,
+        growth: SEEDLING_GROWTH,
+    };
+
+    spawn_plant(&mut commands, &mut materials, &plant_assets, &tile_settings, tile, plant);
+}
+
+fn grow_plants(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+    mut growth: ResMut<PlantGrowth>,
+    season: Res<CurrentSeason>,
+    mut plant_query: Query<(Entity, &mut Plant)>,
+    tile_query: Query<&Tile>,
+    plant_assets: Res<PlantAssets>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+) {
+    if !growth.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut occupied: HashSet<IVec2> = plant_query.iter().map(|(_, plant)| plant.coords).collect();
+
+    for (entity, mut plant) in plant_query.iter_mut() {
+        // plants wither when the water they need is filled in
+        if !plant.species.can_grow(plant.coords, &ponds) {
+            occupied.remove(&plant.coords);
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // nothing grows or spreads through the winter
+        let season_growth = season.0.plant_growth();
+
+        if season_growth > 0.0 {
+            plant.growth = (plant.growth + GROWTH_PER_TICK * season_growth).min(1.0);
+        }
+
+        if plant.growth < 1.0 || !rng.gen_bool((SPREAD_CHANCE * season_growth as f64).min(1.0)) {
+            continue;
+        }
+
+        let candidates: Vec<IVec2> = NEIGHBOURS
+            .iter()
+            .map(|offset| plant.coords + *offset)
+            .filter(|coords| !occupied.contains(coords) && plant.species.can_grow(*coords, &ponds))
+            .collect();
+
+        let Some(&coords) = candidates.choose(&mut rng) else {
+            continue;
+        };
+
+        let Some(tile) = tile_query
+            .iter()
+            .find(|tile| tile_settings.grid_coords(to_top_down(tile.position)) == coords) else {
+            continue;
+        };
+
+        let seedling = Plant {
+            species: plant.species,
+            coords,
+            growth: SEEDLING_GROWTH,
+        };
+
+        occupied.insert(coords);
+        spawn_plant(&mut commands, &mut materials, &plant_assets, &tile_settings, tile, seedling);
+    }
+}
+
+fn scale_plants(mut plant_query: Query<(&Plant, &mut Transform), Changed<Plant>>) {
+    for (plant, mut transform) in plant_query.iter_mut() {
+        transform.scale = Vec3::splat(plant.growth);
+    }
+}
+
+fn load_plants(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut garden_loaded: EventReader<GardenLoaded>,
+    tile_query: Query<&Tile>,
+    plant_query: Query<Entity, With<Plant>>,
+    plant_assets: Res<PlantAssets>,
+    tile_settings: Res<TileSettings>,
+) {
+    let Some(GardenLoaded(save)) = garden_loaded.read().last() else {
+        return;
+    };
+
+    for entity in plant_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for record in save.plants.iter() {
+        let coords = IVec2::from(record.coords);
+
+        let Some(tile) = tile_query
+            .iter()
+            .find(|tile| tile_settings.grid_coords(to_top_down(tile.position)) == coords) else {
+            continue;
+        };
+
+        let plant = Plant {
+            species: record.species,
+            coords,
+            growth: record.growth,
+        };
+
+        spawn_plant(&mut commands, &mut materials, &plant_assets, &tile_settings, tile, plant);
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use bevy::prelude::*;
+
+use super::{equipment::{Equipment, EquipmentType}, koi::Koi, plant::Plant, save::GardenLoaded, season::CurrentSeason, tile::{load_tiles, to_top_down, Tile, TileSettings, TileType}, water::WATER_LEVEL, water_quality::{PondConditions, WaterChemistry}, weather::Weather};
+
+// real seconds between simulation ticks
+const SIMULATION_TICK: f32 = 1.0;
+const SIMULATED_SECONDS_PER_TICK: f32 = 60.0;
+// ponds are considered full once they hold this many koi per tile
+const MAX_KOI_PER_TILE: usize = 4;
+// water temperature in degrees Celsius at which the shallows ice over
+const FREEZING_TEMPERATURE: f32 = 1.0;
+// metres above its normal level a pond can fill before rain runs over the edge
+const MAX_RISE: f32 = 0.15;
+// fraction of the extra water that seeps away per simulated second
+const DRAIN_RATE: f32 = 0.002;
+
+/// Offsets to the orthogonally adjacent tiles.
+pub const NEIGHBOURS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+
+/// A body of water made up of orthogonally connected water tiles.
+#[derive(Debug, Clone)]
+pub struct Pond {
+    pub id: u32,
+    pub tiles: HashSet<IVec2>,
+    pub chemistry: WaterChemistry,
+    /// Metres the water stands above its normal level after rain.
+    pub level: f32,
+    // rotting food collected since the last tick
+    waste: f32,
+}
+
+impl Pond {
+    /// Tiles on the edge of a pond are shallow.
+    pub fn is_shallow(&self, coords: IVec2) -> bool {
+        self.tiles.contains(&coords) && NEIGHBOURS.iter().any(|offset| !self.tiles.contains(&(coords + *offset)))
+    }
+
+    /// Whether any of the pond is away from the edge, ponds one or two tiles wide are all shallows.
+    pub fn has_deep_water(&self) -> bool {
+        self.tiles.iter().any(|coords| !self.is_shallow(*coords))
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.chemistry.temperature <= FREEZING_TEMPERATURE
+    }
+
+    /// Whether the tile at `coords` is iced over, only the shallows freeze so koi can shelter in
+    /// deep water below the ice, a pond with no deep water freezes over completely.
+    pub fn is_iced(&self, coords: IVec2) -> bool {
+        self.is_frozen() && self.is_shallow(coords)
+    }
+
+    /// Number of koi the pond can hold before it is overcrowded.
+    pub fn fish_capacity(&self) -> usize {
+        self.tiles.len() * MAX_KOI_PER_TILE
+    }
+
+    /// Depth of the water in metres, the pond floor sits at the ground plane.
+    pub fn depth(&self) -> f32 {
+        WATER_LEVEL + self.level
+    }
+
+    /// Volume of the pond in cubic metres.
+    pub fn volume(&self, tile_settings: &TileSettings) -> f32 {
+        self.tiles.len() as f32 * tile_settings.tile_size * tile_settings.tile_size * self.depth()
+    }
+
+    /// Rain fills the pond and mixes fresh water into it, whatever doesn't fit runs over the
+    /// edge, then the extra water slowly seeps away again.
+    fn take_rain(&mut self, rainfall: f32, delta: f32) {
+        let rain = rainfall * delta;
+
+        if rain > 0.0 {
+            self.chemistry.dilute(rain / (self.depth() + rain));
+        }
+
+        self.level = (self.level + rain).min(MAX_RISE);
+        self.level -= self.level * (DRAIN_RATE * delta).min(1.0);
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Ponds {
+    ponds: Vec<Pond>,
+    next_id: u32,
+}
+
+impl Ponds {
+    pub fn iter(&self) -> impl Iterator<Item = &Pond> {
+        self.ponds.iter()
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Pond> {
+        self.ponds.iter().find(|pond| pond.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut Pond> {
+        self.ponds.iter_mut().find(|pond| pond.id == id)
+    }
+
+    pub fn pond_at(&self, coords: IVec2) -> Option<&Pond> {
+        self.ponds.iter().find(|pond| pond.tiles.contains(&coords))
+    }
+
+    /// Returns the pond containing the tile at `coords`, or failing that a pond next to it.
+    pub fn pond_touching(&self, coords: IVec2) -> Option<&Pond> {
+        self.pond_at(coords).or_else(|| {
+            NEIGHBOURS.iter().find_map(|offset| self.pond_at(coords + *offset))
+        })
+    }
+
+    pub fn add_waste(&mut self, coords: IVec2, amount: f32) {
+        if let Some(pond) = self.ponds.iter_mut().find(|pond| pond.tiles.contains(&coords)) {
+            pond.waste += amount;
+        }
+    }
+
+    /// Regroups the water tiles into ponds. Ponds keep their id and chemistry as long as they
+    /// overlap their previous tiles, and a pond that is split in two hands its water to both halves.
+    fn rebuild(&mut self, water_tiles: &HashSet<IVec2>) {
+        let previous = std::mem::take(&mut self.ponds);
+        let mut unvisited = water_tiles.clone();
+
+        loop {
+            let Some(start) = unvisited.iter().next().copied() else {
+                break;
+            };
+
+            unvisited.remove(&start);
+
+            let mut tiles = HashSet::from([start]);
+            let mut queue = VecDeque::from([start]);
+
+            while let Some(coords) = queue.pop_front() {
+                for neighbour in NEIGHBOURS.map(|offset| coords + offset) {
+                    if unvisited.remove(&neighbour) {
+                        tiles.insert(neighbour);
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+
+            let ancestor = previous
+                .iter()
+                .max_by_key(|pond| pond.tiles.intersection(&tiles).count())
+                .filter(|pond| !pond.tiles.is_disjoint(&tiles));
+
+            let id = match ancestor {
+                Some(ancestor) if self.get(ancestor.id).is_none() => ancestor.id,
+                _ => {
+                    self.next_id += 1;
+                    self.next_id
+                }
+            };
+
+            self.ponds.push(Pond {
+                id,
+                tiles,
+                chemistry: ancestor.map(|pond| pond.chemistry).unwrap_or_default(),
+                level: ancestor.map(|pond| pond.level).unwrap_or_default(),
+                waste: 0.0,
+            });
+        }
+
+        self.ponds.sort_by_key(|pond| pond.id);
+    }
+}
+
+#[derive(Resource)]
+struct PondSimulation {
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct PondStatsDisplay;
+
+pub struct PondPlugin;
+
+impl Plugin for PondPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Ponds::default())
+            .insert_resource(PondSimulation {
+                timer: Timer::from_seconds(SIMULATION_TICK, TimerMode::Repeating),
+            })
+            .add_systems(Startup, setup)
+            .add_systems(Update, (
+                detect_ponds.after(load_tiles),
+                load_ponds.after(detect_ponds),
+                simulate.after(load_ponds),
+                display_stats.after(simulate),
+            ));
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.),
+            right: Val::Px(15.),
+            ..default()
+        }),
+        PondStatsDisplay,
+    ));
+}
+
+pub fn detect_ponds(
+    mut ponds: ResMut<Ponds>,
+    tile_settings: Res<TileSettings>,
+    changed_query: Query<(), Changed<Tile>>,
+    tile_query: Query<&Tile>,
+) {
+    if changed_query.is_empty() {
+        return;
+    }
+
+    let water_tiles = tile_query
+        .iter()
+        .filter(|tile| tile.tile_type == TileType::Water)
+        .map(|tile| tile_settings.grid_coords(to_top_down(tile.position)))
+        .collect();
+
+    ponds.rebuild(&water_tiles);
+}
+
+/// Restores the chemistry and water level of each pond in a loaded garden.
+fn load_ponds(mut garden_loaded: EventReader<GardenLoaded>, mut ponds: ResMut<Ponds>) {
+    let Some(GardenLoaded(save)) = garden_loaded.read().last() else {
+        return;
+    };
+
+    for record in save.ponds.iter() {
+        let coords = IVec2::from(record.tile);
+
+        if let Some(pond) = ponds.ponds.iter_mut().find(|pond| pond.tiles.contains(&coords)) {
+            pond.chemistry = record.chemistry;
+            pond.level = record.level;
+        }
+    }
+}
+
+fn simulate(
+    time: Res<Time>,
+    mut simulation: ResMut<PondSimulation>,
+    mut ponds: ResMut<Ponds>,
+    tile_settings: Res<TileSettings>,
+    season: Res<CurrentSeason>,
+    weather: Res<Weather>,
+    koi_query: Query<&Transform, With<Koi>>,
+    equipment_query: Query<&Equipment>,
+    plant_query: Query<&Plant>,
+) {
+    if !simulation.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let mut fish_counts: HashMap<u32, u32> = HashMap::new();
+
+    for transform in koi_query.iter() {
+        if let Some(pond) = ponds.pond_at(tile_settings.grid_coords(to_top_down(transform.translation))) {
+            *fish_counts.entry(pond.id).or_default() += 1;
+        }
+    }
+
+    // total capacity of each kind of equipment serving each pond
+    let mut equipment_capacity: HashMap<(u32, EquipmentType), f32> = HashMap::new();
+
+    for equipment in equipment_query.iter() {
+        if let Some(pond) = ponds.pond_touching(equipment.coords) {
+            *equipment_capacity.entry((pond.id, equipment.equipment_type)).or_default() += equipment.capacity();
+        }
+    }
+
+    // surface area shaded by plants in or around each pond, in tiles
+    let mut plant_cover: HashMap<u32, f32> = HashMap::new();
+
+    for plant in plant_query.iter() {
+        if let Some(pond) = ponds.pond_touching(plant.coords) {
+            *plant_cover.entry(pond.id).or_default() += plant.species.cover() * plant.growth;
+        }
+    }
+
+    for pond in ponds.ponds.iter_mut() {
+        let pond_id = pond.id;
+        let volume = pond.volume(&tile_settings);
+        let capacity = |equipment_type: EquipmentType| {
+            equipment_capacity.get(&(pond_id, equipment_type)).copied().unwrap_or_default() / volume
+        };
+
+        let conditions = PondConditions {
+            volume,
+            fish_count: fish_counts.get(&pond_id).copied().unwrap_or_default(),
+            waste: std::mem::take(&mut pond.waste),
+            filtration: capacity(EquipmentType::Filter),
+            aeration: capacity(EquipmentType::Aerator),
+            flow: capacity(EquipmentType::Pump),
+            plant_cover: (plant_cover.get(&pond_id).copied().unwrap_or_default() / pond.tiles.len() as f32).min(1.0),
+            air_temperature: season.0.air_temperature(),
+        };
+
+        pond.chemistry.step(&conditions, SIMULATED_SECONDS_PER_TICK);
+        pond.take_rain(weather.rainfall(), SIMULATED_SECONDS_PER_TICK);
+    }
+}
+
+fn display_stats(ponds: Res<Ponds>, mut text_query: Query<&mut Text, With<PondStatsDisplay>>) {
+    if !ponds.is_changed() {
+        return;
+    }
+
+    let text = &mut text_query.single_mut();
+
+    text.sections[0].value = ponds
+        .iter()
+        .map(|pond| {
+            let chemistry = &pond.chemistry;
+
+            format!(
+                "Pond {}: O2 {:.1} mg/L | NH3 {:.2} ppm | NO3 {:.1} ppm | {:.1} C | Clarity {:.0}%{}{}",
+                pond.id,
+                chemistry.oxygen,
+                chemistry.ammonia,
+                chemistry.nitrate,
+                chemistry.temperature,
+                chemistry.clarity * 100.0,
+                if pond.level >= 0.01 { format!(" | +{:.2} m", pond.level) } else { String::new() },
+                if pond.is_frozen() { " | Iced over" } else { "" },
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+}
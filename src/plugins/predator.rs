@@ -0,0 +1,288 @@
+use std::{collections::HashMap, fmt};
+
+use bevy::prelude::*;
+use rand::{seq::IteratorRandom, Rng};
+
+use super::{decoration::{Decoration, DecorationKind}, koi::Koi, notification::Notification, plant::Plant, pond::{Pond, Ponds}, tile::{to_top_down, TileSettings}};
+
+// real seconds between chances of a raid
+const RAID_INTERVAL: f32 = 90.0;
+const RAID_CHANCE: f64 = 0.5;
+// real seconds between a predator being spotted and the raid being over
+const RAID_DURATION: f32 = 8.0;
+const REPORT_DURATION: f32 = 20.0;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Predator {
+    Heron,
+    Raccoon,
+}
+
+impl Predator {
+    fn max_catches(&self) -> usize {
+        match self {
+            Predator::Heron => 2,
+            Predator::Raccoon => 3,
+        }
+    }
+
+    /// Chance of catching a koi in the shallows of an unprotected pond.
+    fn base_chance(&self) -> f32 {
+        match self {
+            Predator::Heron => 0.5,
+            Predator::Raccoon => 0.4,
+        }
+    }
+
+    /// How much of the base chance remains against koi in deep water.
+    fn deep_water_reach(&self) -> f32 {
+        match self {
+            Predator::Heron => 0.2,
+            // raccoons only fish from the bank
+            Predator::Raccoon => 0.0,
+        }
+    }
+
+    /// How well plant cover hides koi from the predator, from 0.0 to 1.0.
+    fn thwarted_by_cover(&self) -> f32 {
+        match self {
+            // herons hunt by sight from above
+            Predator::Heron => 0.8,
+            Predator::Raccoon => 0.3,
+        }
+    }
+}
+
+impl fmt::Display for Predator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Predator::Heron => write!(f, "heron"),
+            Predator::Raccoon => write!(f, "raccoon"),
+        }
+    }
+}
+
+/// A pond's defences against predators, each from 0.0 (none) to 1.0 (complete).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PondDefences {
+    /// Share of the pond that is deep enough for koi to retreat to.
+    pub depth: f32,
+    /// Share of the pond covered by netting.
+    pub netting: f32,
+    pub plant_cover: f32,
+}
+
+impl PondDefences {
+    /// Chance `predator` catches a koi, which is either in the shallows or in deep water.
+    pub fn catch_chance(&self, predator: Predator, in_shallows: bool) -> f32 {
+        let reach = if in_shallows {
+            // koi in the shallows can still flee if there is deep water to flee to
+            1.0 - self.depth * 0.5
+        } else {
+            predator.deep_water_reach()
+        };
+
+        predator.base_chance()
+            * reach
+            * (1.0 - self.netting)
+            * (1.0 - self.plant_cover * predator.thwarted_by_cover())
+    }
+}
+
+struct Raid {
+    predator: Predator,
+    pond_id: u32,
+    timer: Timer,
+}
+
+#[derive(Resource)]
+struct PredatorWatch {
+    timer: Timer,
+    raid: Option<Raid>,
+}
+
+#[derive(Component)]
+struct RaidReport {
+    timer: Timer,
+}
+
+pub struct PredatorPlugin;
+
+impl Plugin for PredatorPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(PredatorWatch {
+                timer: Timer::from_seconds(RAID_INTERVAL, TimerMode::Repeating),
+                raid: None,
+            })
+            .add_systems(Startup, setup)
+            .add_systems(Update, (start_raid, finish_raid, hide_report));
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::rgb_u8(255, 200, 160),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(50.),
+            left: Val::Percent(35.),
+            ..default()
+        }),
+        RaidReport {
+            timer: Timer::from_seconds(REPORT_DURATION, TimerMode::Once),
+        },
+    ));
+}
+
+fn pond_defences(
+    pond: &Pond,
+    ponds: &Ponds,
+    decoration_query: &Query<&Decoration>,
+    plant_query: &Query<&Plant>,
+) -> PondDefences {
+    let serves_pond = |coords: IVec2| ponds.pond_touching(coords).is_some_and(|touching| touching.id == pond.id);
+
+    let deep_tiles = pond.tiles.iter().filter(|coords| !pond.is_shallow(**coords)).count();
+
+    let netted_tiles = decoration_query
+        .iter()
+        .filter(|decoration| decoration.kind == DecorationKind::Netting)
+        .flat_map(|decoration| decoration.tiles.iter())
+        .filter(|coords| pond.tiles.contains(coords))
+        .count();
+
+    let plant_cover: f32 = plant_query
+        .iter()
+        .filter(|plant| serves_pond(plant.coords))
+        .map(|plant| plant.species.cover() * plant.growth)
+        .sum();
+
+    PondDefences {
+        depth: deep_tiles as f32 / pond.tiles.len() as f32,
+        netting: (netted_tiles as f32 / pond.tiles.len() as f32).min(1.0),
+        plant_cover: (plant_cover / pond.tiles.len() as f32).min(1.0),
+    }
+}
+
+fn start_raid(
+    mut notifications: EventWriter<Notification>,
+    time: Res<Time>,
+    mut watch: ResMut<PredatorWatch>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+    koi_query: Query<&Transform, With<Koi>>,
+) {
+    if !watch.timer.tick(time.delta()).just_finished() || watch.raid.is_some() {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+
+    if !rng.gen_bool(RAID_CHANCE) {
+        return;
+    }
+
+    // predators only bother with ponds that have koi in them
+    let Some(pond_id) = koi_query
+        .iter()
+        .filter_map(|transform| ponds.pond_at(tile_settings.grid_coords(to_top_down(transform.translation))))
+        .map(|pond| pond.id)
+        .choose(&mut rng) else {
+        return;
+    };
+
+    let predator = if rng.gen_bool(0.5) { Predator::Heron } else { Predator::Raccoon };
+
+    notifications.send(Notification(format!("A {} is raiding pond {}!", predator, pond_id)));
+
+    watch.raid = Some(Raid {
+        predator,
+        pond_id,
+        timer: Timer::from_seconds(RAID_DURATION, TimerMode::Once),
+    });
+}
+
+fn finish_raid(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut watch: ResMut<PredatorWatch>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+    koi_query: Query<(Entity, &Koi, &Transform)>,
+    decoration_query: Query<&Decoration>,
+    plant_query: Query<&Plant>,
+    mut report_query: Query<(&mut Text, &mut RaidReport)>,
+) {
+    let Some(raid) = watch.raid.as_mut() else {
+        return;
+    };
+
+    if !raid.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let Some(raid) = watch.raid.take() else {
+        return;
+    };
+
+    let mut rng = rand::thread_rng();
+    let mut lost = Vec::new();
+
+    if let Some(pond) = ponds.get(raid.pond_id) {
+        let defences = pond_defences(pond, &ponds, &decoration_query, &plant_query);
+        let mut chances: HashMap<Entity, f32> = HashMap::new();
+
+        for (entity, _, transform) in koi_query.iter() {
+            let coords = tile_settings.grid_coords(to_top_down(transform.translation));
+
+            if pond.tiles.contains(&coords) {
+                chances.insert(entity, defences.catch_chance(raid.predator, pond.is_shallow(coords)));
+            }
+        }
+
+        for (entity, chance) in chances {
+            if lost.len() >= raid.predator.max_catches() {
+                break;
+            }
+
+            if rng.gen::<f32>() < chance {
+                lost.push(entity);
+            }
+        }
+    }
+
+    let lost_names: Vec<String> = lost
+        .iter()
+        .filter_map(|entity| koi_query.get(*entity).ok())
+        .map(|(_, koi, _)| format!("{} the {}", koi.name, koi.variety()))
+        .collect();
+
+    for entity in lost {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let (mut text, mut report) = report_query.single_mut();
+
+    text.sections[0].value = if lost_names.is_empty() {
+        format!("The {} left pond {} empty handed", raid.predator, raid.pond_id)
+    } else {
+        format!("The {} raided pond {} and took:\n{}", raid.predator, raid.pond_id, lost_names.join("\n"))
+    };
+    report.timer.reset();
+}
+
+fn hide_report(time: Res<Time>, mut report_query: Query<(&mut Text, &mut RaidReport)>) {
+    let (mut text, mut report) = report_query.single_mut();
+
+    if report.timer.tick(time.delta()).just_finished() {
+        text.sections[0].value.clear();
+    }
+}
@@ -0,0 +1,303 @@
+use std::{collections::HashMap, fs};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use super::{camera_controller::CameraBookmarks, decoration::{Decoration, DecorationKind}, disease::{Illness, PondTreatments, Sick}, economy::Wallet, equipment::{Equipment, EquipmentType}, gardener::{Gardener, TaskKind, TaskQueue}, koi::Koi, light::TimeOfDay, market::{Market, Offer}, notification::Notification, pedigree::Pedigree, plant::{Plant, PlantSpecies}, pond::Ponds, tile::{to_top_down, Tile, TileSettings, TileType}, visitor::Reputation, weather::Weather};
+
+const SAVE_PATH: &str = "garden.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileRecord {
+    pub coords: [i32; 2],
+    pub tile_type: TileType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecorationRecord {
+    pub kind: DecorationKind,
+    pub coords: [i32; 2],
+    pub rotation: u8,
+    pub tiles: Vec<[i32; 2]>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KoiRecord {
+    pub koi: Koi,
+    /// Top down world position.
+    pub position: [f32; 2],
+    #[serde(default)]
+    pub illness: Option<Illness>,
+    #[serde(default)]
+    pub medicated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PondRecord {
+    /// Any tile of the pond, ponds are numbered afresh when the tiles are loaded so they are
+    /// matched up through their tiles instead.
+    pub tile: [i32; 2],
+    pub chemistry: WaterChemistry,
+    pub level: f32,
+    /// Disease ticks of salt left in the pond.
+    pub salt: u32,
+    pub quarantine: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlantRecord {
+    pub species: PlantSpecies,
+    pub coords: [i32; 2],
+    pub growth: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquipmentRecord {
+    pub equipment_type: EquipmentType,
+    pub coords: [i32; 2],
+    pub clogging: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkRecord {
+    pub slot: u8,
+    pub focus: [f32; 3],
+    pub yaw: f32,
+    pub pitch: f32,
+    pub radius: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GardenerRecord {
+    pub position: [f32; 3],
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TimeOfDayRecord {
+    #[serde(default)]
+    pub day: u32,
+    pub hour: f32,
+    pub speed: f32,
+    pub paused: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WeatherRecord {
+    pub seed: u64,
+    pub changes: u64,
+}
+
+/// Everything needed to restore a garden, written to and read from a JSON file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GardenSave {
+    pub tiles: Vec<TileRecord>,
+    pub decorations: Vec<DecorationRecord>,
+    pub koi: Vec<KoiRecord>,
+    pub plants: Vec<PlantRecord>,
+    pub equipment: Vec<EquipmentRecord>,
+    pub balance: u32,
+    pub sandbox: bool,
+    pub pedigree: Pedigree,
+    pub market_seed: u64,
+    pub market_restocks: u64,
+    /// Offers left at the market, `None` in saves from before they were kept.
+    #[serde(default)]
+    pub market_stock: Option<Vec<Offer>>,
+    #[serde(default)]
+    pub market_price_history: Vec<u32>,
+    #[serde(default)]
+    pub camera_bookmarks: Vec<BookmarkRecord>,
+    #[serde(default)]
+    pub time_of_day: Option<TimeOfDayRecord>,
+    #[serde(default)]
+    pub weather: Option<WeatherRecord>,
+    #[serde(default)]
+    pub ponds: Vec<PondRecord>,
+    #[serde(default)]
+    pub gardeners: Vec<GardenerRecord>,
+    #[serde(default)]
+    pub task_priorities: HashMap<TaskKind, u32>,
+    #[serde(default)]
+    pub reputation: Option<f32>,
+}
+
+/// Sent when a save has been read, each plugin restores its own part of the garden from it.
+#[derive(Event, Debug)]
+pub struct GardenLoaded(pub GardenSave);
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<GardenLoaded>()
+            // loaded ahead of the frame so every plugin restores its part from the same tiles
+            .add_systems(PreUpdate, load_garden)
+            .add_systems(Update, save_garden);
+    }
+}
+
+/// The gardeners and the garden's standing with visitors, kept together to leave room in
+/// `save_garden` for everything else.
+#[derive(SystemParam)]
+struct Staff<'w, 's> {
+    queue: Res<'w, TaskQueue>,
+    reputation: Res<'w, Reputation>,
+    gardener_query: Query<'w, 's, &'static Transform, With<Gardener>>,
+}
+
+fn save_garden(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut notifications: EventWriter<Notification>,
+    wallet: Res<Wallet>,
+    pedigree: Res<Pedigree>,
+    market: Res<Market>,
+    bookmarks: Res<CameraBookmarks>,
+    time_of_day: Res<TimeOfDay>,
+    weather: Res<Weather>,
+    (ponds, treatments): (Res<Ponds>, Res<PondTreatments>),
+    staff: Staff,
+    tile_settings: Res<TileSettings>,
+    tile_query: Query<&Tile>,
+    decoration_query: Query<&Decoration>,
+    koi_query: Query<(&Koi, &Transform, Option<&Sick>)>,
+    plant_query: Query<&Plant>,
+    equipment_query: Query<&Equipment>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F8) {
+        return;
+    }
+
+    let save = GardenSave {
+        tiles: tile_query
+            .iter()
+            .map(|tile| TileRecord {
+                coords: tile_settings.grid_coords(to_top_down(tile.position)).to_array(),
+                tile_type: tile.tile_type,
+            })
+            .collect(),
+        decorations: decoration_query
+            .iter()
+            .map(|decoration| DecorationRecord {
+                kind: decoration.kind,
+                coords: decoration.coords.to_array(),
+                rotation: decoration.rotation,
+                tiles: decoration.tiles.iter().map(|coords| coords.to_array()).collect(),
+            })
+            .collect(),
+        koi: koi_query
+            .iter()
+            .map(|(koi, transform, sick)| KoiRecord {
+                koi: koi.clone(),
+                position: to_top_down(transform.translation).to_array(),
+                illness: sick.map(|sick| sick.illness),
+                medicated: sick.is_some_and(|sick| sick.medicated),
+            })
+            .collect(),
+        plants: plant_query
+            .iter()
+            .map(|plant| PlantRecord {
+                species: plant.species,
+                coords: plant.coords.to_array(),
+                growth: plant.growth,
+            })
+            .collect(),
+        equipment: equipment_query
+            .iter()
+            .map(|equipment| EquipmentRecord {
+                equipment_type: equipment.equipment_type,
+                coords: equipment.coords.to_array(),
+                clogging: equipment.clogging,
+            })
+            .collect(),
+        balance: wallet.balance(),
+        sandbox: wallet.sandbox,
+        pedigree: pedigree.clone(),
+        market_seed: market.seed,
+        market_restocks: market.restocks,
+        market_stock: Some(market.stock.clone()),
+        market_price_history: market.price_history.iter().copied().collect(),
+        camera_bookmarks: bookmarks
+            .slots
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, view)| {
+                view.map(|view| BookmarkRecord {
+                    slot: slot as u8,
+                    focus: view.focus.to_array(),
+                    yaw: view.yaw,
+                    pitch: view.pitch,
+                    radius: view.radius,
+                })
+            })
+            .collect(),
+        time_of_day: Some(TimeOfDayRecord {
+            day: time_of_day.day,
+            hour: time_of_day.hour,
+            speed: time_of_day.speed,
+            paused: time_of_day.paused,
+        }),
+        weather: Some(WeatherRecord {
+            seed: weather.seed,
+            changes: weather.changes,
+        }),
+        ponds: ponds
+            .iter()
+            .filter_map(|pond| {
+                let tile = pond.tiles.iter().min_by_key(|coords| (coords.x, coords.y))?;
+
+                Some(PondRecord {
+                    tile: tile.to_array(),
+                    chemistry: pond.chemistry,
+                    level: pond.level,
+                    salt: treatments.salt_left(pond.id),
+                    quarantine: treatments.quarantine == Some(pond.id),
+                })
+            })
+            .collect(),
+        gardeners: staff
+            .gardener_query
+            .iter()
+            .map(|transform| GardenerRecord {
+                position: transform.translation.to_array(),
+            })
+            .collect(),
+        task_priorities: staff.queue.priorities.clone(),
+        reputation: Some(staff.reputation.0),
+    };
+
+    let result = serde_json::to_string_pretty(&save)
+        .map_err(|error| error.to_string())
+        .and_then(|contents| fs::write(SAVE_PATH, contents).map_err(|error| error.to_string()));
+
+    let message = match result {
+        Ok(()) => format!("Saved the garden to {}", SAVE_PATH),
+        Err(error) => format!("Could not save the garden to {}: {}", SAVE_PATH, error),
+    };
+
+    notifications.send(Notification(message));
+}
+
+fn load_garden(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut notifications: EventWriter<Notification>,
+    mut garden_loaded: EventWriter<GardenLoaded>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F9) {
+        return;
+    }
+
+    let result = fs::read_to_string(SAVE_PATH)
+        .map_err(|error| error.to_string())
+        .and_then(|contents| serde_json::from_str::<GardenSave>(&contents).map_err(|error| error.to_string()));
+
+    match result {
+        Ok(save) => {
+            garden_loaded.send(GardenLoaded(save));
+            notifications.send(Notification(format!("Loaded the garden from {}", SAVE_PATH)));
+        }
+        Err(error) => {
+            notifications.send(Notification(format!("Could not load the garden from {}: {}", SAVE_PATH, error)));
+        }
+    }
+}
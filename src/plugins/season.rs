@@ -0,0 +1,211 @@
+use std::{collections::{HashMap, HashSet}, fmt};
+
+use bevy::{pbr::NotShadowCaster, prelude::*};
+
+use super::{light::TimeOfDay, notification::Notification, pond::Ponds, tile::TileSettings};
+
+// days on the garden clock each season lasts
+const DAYS_PER_SEASON: u32 = 3;
+const ICE_THICKNESS: f32 = 0.1;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    const ALL: [Season; 4] = [Season::Spring, Season::Summer, Season::Autumn, Season::Winter];
+
+    /// The season on a given day of the garden clock, the first day is the start of spring.
+    pub fn on_day(day: u32) -> Self {
+        Self::ALL[(day / DAYS_PER_SEASON) as usize % Self::ALL.len()]
+    }
+
+    /// Air temperature in degrees Celsius the ponds warm or cool towards.
+    pub fn air_temperature(&self) -> f32 {
+        match self {
+            Season::Spring => 16.0,
+            Season::Summer => 26.0,
+            Season::Autumn => 11.0,
+            Season::Winter => -4.0,
+        }
+    }
+
+    pub fn grass_color(&self) -> Color {
+        match self {
+            Season::Spring => Color::rgba_u8(150, 210, 110, 255),
+            Season::Summer => Color::rgba_u8(179, 202, 130, 255),
+            Season::Autumn => Color::rgba_u8(196, 172, 102, 255),
+            Season::Winter => Color::rgba_u8(214, 222, 220, 255),
+        }
+    }
+
+    /// Tone the daytime ambient light is blended towards, a touch of green in spring, golden in
+    /// autumn and cold in winter.
+    pub fn ambient_tone(&self) -> Color {
+        match self {
+            Season::Spring => Color::rgb(0.8, 0.95, 0.6),
+            Season::Summer => Color::rgb(0.94, 0.89, 0.3),
+            Season::Autumn => Color::rgb(1.0, 0.7, 0.35),
+            Season::Winter => Color::rgb(0.75, 0.85, 1.0),
+        }
+    }
+
+    /// How quickly koi get hungry compared to summer, they barely eat in the cold.
+    pub fn appetite(&self) -> f32 {
+        match self {
+            Season::Spring => 0.8,
+            Season::Summer => 1.0,
+            Season::Autumn => 0.6,
+            Season::Winter => 0.2,
+        }
+    }
+
+    /// How likely koi are to breed compared to the rest of the year, they spawn in spring.
+    pub fn breeding(&self) -> f64 {
+        match self {
+            Season::Spring => 2.0,
+            Season::Summer => 1.0,
+            Season::Autumn => 0.25,
+            Season::Winter => 0.0,
+        }
+    }
+
+    /// How quickly plants grow and spread compared to summer.
+    pub fn plant_growth(&self) -> f32 {
+        match self {
+            Season::Spring => 1.5,
+            Season::Summer => 1.0,
+            Season::Autumn => 0.4,
+            Season::Winter => 0.0,
+        }
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Season::Spring => write!(f, "Spring"),
+            Season::Summer => write!(f, "Summer"),
+            Season::Autumn => write!(f, "Autumn"),
+            Season::Winter => write!(f, "Winter"),
+        }
+    }
+}
+
+/// The season the garden is in, follows the day count on the garden clock.
+#[derive(Resource, Debug, PartialEq)]
+pub struct CurrentSeason(pub Season);
+
+/// Sheet of ice over the tile at the given coordinates.
+#[derive(Component)]
+struct Ice(IVec2);
+
+#[derive(Resource)]
+struct IceAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub struct SeasonPlugin;
+
+impl Plugin for SeasonPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(CurrentSeason(Season::on_day(0)))
+            .add_systems(Startup, setup)
+            .add_systems(Update, (change_season, freeze_ponds));
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    tile_settings: Res<TileSettings>,
+) {
+    commands.insert_resource(IceAssets {
+        mesh: meshes.add(Cuboid::new(tile_settings.tile_size, ICE_THICKNESS, tile_settings.tile_size)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.85, 0.93, 1.0, 0.8),
+            alpha_mode: AlphaMode::Blend,
+            perceptual_roughness: 0.2,
+            ..default()
+        }),
+    });
+}
+
+fn change_season(
+    mut notifications: EventWriter<Notification>,
+    time_of_day: Res<TimeOfDay>,
+    mut season: ResMut<CurrentSeason>,
+) {
+    let current = Season::on_day(time_of_day.day);
+
+    if season.0 != current {
+        season.0 = current;
+        notifications.send(Notification(format!("{} has come to the garden", current)));
+    }
+}
+
+/// Covers the iced over shallows of frozen ponds with sheets of ice, floating on the water as
+/// it rises and falls.
+fn freeze_ponds(
+    mut commands: Commands,
+    mut iced: Local<HashSet<IVec2>>,
+    ponds: Res<Ponds>,
+    ice_assets: Res<IceAssets>,
+    tile_settings: Res<TileSettings>,
+    mut ice_query: Query<(Entity, &Ice, &mut Transform)>,
+) {
+    if !ponds.is_changed() {
+        return;
+    }
+
+    let now_iced: HashMap<IVec2, f32> = ponds
+        .iter()
+        .flat_map(|pond| {
+            pond.tiles
+                .iter()
+                .copied()
+                .filter(|coords| pond.is_iced(*coords))
+                .map(|coords| (coords, pond.depth() + ICE_THICKNESS / 2.0))
+        })
+        .collect();
+
+    if now_iced.len() == iced.len() && now_iced.keys().all(|coords| iced.contains(coords)) {
+        for (_, ice, mut transform) in ice_query.iter_mut() {
+            if let Some(height) = now_iced.get(&ice.0).copied() {
+                if transform.translation.y != height {
+                    transform.translation.y = height;
+                }
+            }
+        }
+
+        return;
+    }
+
+    for (entity, ..) in ice_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for (coords, height) in now_iced.iter() {
+        let position = tile_settings.world_position(*coords);
+
+        commands.spawn((
+            PbrBundle {
+                mesh: ice_assets.mesh.clone(),
+                material: ice_assets.material.clone(),
+                transform: Transform::from_xyz(position.x, *height, position.y),
+                ..default()
+            },
+            NotShadowCaster,
+            Ice(*coords),
+        ));
+    }
+
+    *iced = now_iced.into_keys().collect();
+}
@@ -0,0 +1,321 @@
+use std::{cmp::Ordering, collections::HashMap, fmt};
+
+use bevy::prelude::*;
+
+use super::{genome::Variety, koi::Koi};
+
+// length in centimetres that earns full marks for size
+const JUMBO_SIZE: f32 = 80.0;
+// share of the total score a koi in poor health can lose
+const CONDITION_PENALTY: f32 = 0.3;
+const SHOWN_PLACINGS: usize = 10;
+
+/// How much each category counts towards the total for a variety, the weights add up to 1.0.
+struct JudgingRules {
+    body: f32,
+    pattern: f32,
+    color: f32,
+    size: f32,
+    /// Ideal red and black coverage, varieties without markings are judged on how clean they are.
+    ideal_red: f32,
+    ideal_black: f32,
+}
+
+impl JudgingRules {
+    fn for_variety(variety: Variety) -> Self {
+        match variety {
+            // the "big three" are won and lost on their patterns
+            Variety::Kohaku => Self { body: 0.2, pattern: 0.4, color: 0.25, size: 0.15, ideal_red: 0.5, ideal_black: 0.0 },
+            Variety::Sanke => Self { body: 0.2, pattern: 0.4, color: 0.25, size: 0.15, ideal_red: 0.45, ideal_black: 0.15 },
+            Variety::Showa => Self { body: 0.2, pattern: 0.35, color: 0.3, size: 0.15, ideal_red: 0.35, ideal_black: 0.35 },
+            // solid koi are judged on skin quality and presence
+            Variety::ShiroMuji | Variety::Karasu => {
+                Self { body: 0.3, pattern: 0.15, color: 0.35, size: 0.2, ideal_red: 0.0, ideal_black: 0.0 }
+            }
+            Variety::Asagi => Self { body: 0.3, pattern: 0.2, color: 0.3, size: 0.2, ideal_red: 0.0, ideal_black: 0.0 },
+            // chagoi are prized for growing huge
+            Variety::Chagoi => Self { body: 0.3, pattern: 0.1, color: 0.2, size: 0.4, ideal_red: 0.0, ideal_black: 0.0 },
+            // ogon are judged on the lustre of their scales
+            Variety::Ogon => Self { body: 0.25, pattern: 0.1, color: 0.45, size: 0.2, ideal_red: 0.0, ideal_black: 0.0 },
+        }
+    }
+}
+
+/// Scores for a single koi, each category ranges from 0.0 to 1.0 and the total from 0.0 to 100.0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scorecard {
+    pub body: f32,
+    pub pattern: f32,
+    pub color: f32,
+    pub size: f32,
+    pub total: f32,
+}
+
+/// Scores a koi against the rules for its variety, the same koi always gets the same scorecard.
+pub fn judge(koi: &Koi) -> Scorecard {
+    let genome = &koi.genome;
+    let rules = JudgingRules::for_variety(koi.variety());
+
+    let coverage_error = (genome.red_coverage - rules.ideal_red).abs() + (genome.black_coverage - rules.ideal_black).abs();
+    let pattern = (genome.pattern_balance * (1.0 - coverage_error)).clamp(0.0, 1.0);
+    let body = genome.body_shape;
+    let color = genome.saturation;
+    let size = (koi.size / JUMBO_SIZE).min(1.0);
+
+    let weighted = body * rules.body + pattern * rules.pattern + color * rules.color + size * rules.size;
+    let condition = 1.0 - (1.0 - koi.health.clamp(0.0, 1.0)) * CONDITION_PENALTY;
+
+    Scorecard {
+        body,
+        pattern,
+        color,
+        size,
+        total: weighted * condition * 100.0,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placing {
+    pub koi_id: u32,
+    pub name: String,
+    pub variety: Variety,
+    pub scorecard: Scorecard,
+}
+
+/// Judges every entrant and ranks them from best to worst, ties go to the koi with the lowest id.
+pub fn rank<'a>(entrants: impl Iterator<Item = &'a Koi>) -> Vec<Placing> {
+    let mut placings: Vec<Placing> = entrants
+        .map(|koi| Placing {
+            koi_id: koi.id,
+            name: koi.name.clone(),
+            variety: koi.variety(),
+            scorecard: judge(koi),
+        })
+        .collect();
+
+    placings.sort_by(|a, b| {
+        b.scorecard
+            .total
+            .partial_cmp(&a.scorecard.total)
+            .unwrap_or(Ordering::Equal)
+            .then(a.koi_id.cmp(&b.koi_id))
+    });
+
+    placings
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Award {
+    GrandChampion,
+    BestInVariety(Variety),
+}
+
+impl fmt::Display for Award {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Award::GrandChampion => write!(f, "Grand Champion"),
+            Award::BestInVariety(variety) => write!(f, "Best {}", variety),
+        }
+    }
+}
+
+/// Hands out awards from a ranking produced by [`rank`], the grand champion does not also take
+/// best in variety.
+pub fn awards(placings: &[Placing]) -> Vec<(Award, &Placing)> {
+    let Some((champion, rest)) = placings.split_first() else {
+        return Vec::new();
+    };
+
+    let mut awards = vec![(Award::GrandChampion, champion)];
+    let mut best_in_variety: HashMap<Variety, &Placing> = HashMap::new();
+
+    for placing in rest {
+        best_in_variety.entry(placing.variety).or_insert(placing);
+    }
+
+    let mut variety_awards: Vec<(Award, &Placing)> = best_in_variety
+        .into_iter()
+        .map(|(variety, placing)| (Award::BestInVariety(variety), placing))
+        .collect();
+
+    // keep the order of the ranking so results read the same every time
+    variety_awards.sort_by_key(|(_, placing)| placings.iter().position(|other| other.koi_id == placing.koi_id));
+    awards.extend(variety_awards);
+    awards
+}
+
+#[derive(Component)]
+struct ShowResults;
+
+pub struct ShowPlugin;
+
+impl Plugin for ShowPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup)
+            .add_systems(Update, hold_show);
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(50.),
+            left: Val::Px(15.),
+            padding: UiRect::all(Val::Px(8.)),
+            display: Display::None,
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.7)),
+        ShowResults,
+    ));
+}
+
+/// Judges every koi in the garden when the results are opened with J.
+fn hold_show(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    koi_query: Query<&Koi>,
+    mut results_query: Query<(&mut Text, &mut Style), With<ShowResults>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyJ) {
+        return;
+    }
+
+    let (mut text, mut style) = results_query.single_mut();
+
+    if style.display != Display::None {
+        style.display = Display::None;
+        return;
+    }
+
+    style.display = Display::Flex;
+
+    let placings = rank(koi_query.iter());
+
+    if placings.is_empty() {
+        text.sections[0].value = "Koi show: no koi entered".into();
+        return;
+    }
+
+    let ranking: Vec<String> = placings
+        .iter()
+        .take(SHOWN_PLACINGS)
+        .enumerate()
+        .map(|(index, placing)| {
+            let scorecard = &placing.scorecard;
+
+            format!(
+                "{}. {} ({}) {:.1}  body {:.2} pattern {:.2} color {:.2} size {:.2}",
+                index + 1,
+                placing.name,
+                placing.variety,
+                scorecard.total,
+                scorecard.body,
+                scorecard.pattern,
+                scorecard.color,
+                scorecard.size,
+            )
+        })
+        .collect();
+
+    let awards: Vec<String> = awards(&placings)
+        .into_iter()
+        .map(|(award, placing)| format!("{}: {}", award, placing.name))
+        .collect();
+
+    text.sections[0].value = format!("Koi show results\n{}\n\nAwards\n{}", ranking.join("\n"), awards.join("\n"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::genome::{BaseColor, Genome};
+
+    fn koi(id: u32, base_color: BaseColor, metallic: bool, size: f32, saturation: f32) -> Koi {
+        let genome = Genome {
+            base_color,
+            metallic,
+            red_coverage: 0.0,
+            black_coverage: 0.0,
+            saturation,
+            pattern_balance: 1.0,
+            body_shape: 0.5,
+            growth_potential: 1.0,
+        };
+
+        let mut koi = Koi::new(format!("Koi {}", id), genome, 0.0);
+        koi.id = id;
+        koi.size = size;
+        koi
+    }
+
+    fn placing(koi_id: u32, variety: Variety, total: f32) -> Placing {
+        Placing {
+            koi_id,
+            name: format!("Koi {}", koi_id),
+            variety,
+            scorecard: Scorecard { body: 0.0, pattern: 0.0, color: 0.0, size: 0.0, total },
+        }
+    }
+
+    #[test]
+    fn judging_is_repeatable() {
+        let entrant = koi(1, BaseColor::White, false, 50.0, 0.7);
+
+        assert_eq!(judge(&entrant), judge(&entrant));
+        assert_eq!(judge(&entrant), judge(&entrant.clone()));
+    }
+
+    #[test]
+    fn variety_weights_decide_the_order() {
+        // a big dull koi against a small vivid one, chagoi are judged on size and ogon on color
+        let big_chagoi = koi(1, BaseColor::Brown, false, JUMBO_SIZE, 0.1);
+        let vivid_chagoi = koi(2, BaseColor::Brown, false, 16.0, 1.0);
+
+        assert!(judge(&big_chagoi).total > judge(&vivid_chagoi).total);
+
+        let big_ogon = koi(3, BaseColor::Brown, true, JUMBO_SIZE, 0.1);
+        let vivid_ogon = koi(4, BaseColor::Brown, true, 16.0, 1.0);
+
+        assert!(judge(&vivid_ogon).total > judge(&big_ogon).total);
+    }
+
+    #[test]
+    fn ties_go_to_the_lowest_id() {
+        let entrants = [koi(7, BaseColor::White, false, 50.0, 0.5), koi(3, BaseColor::White, false, 50.0, 0.5)];
+
+        let placings = rank(entrants.iter());
+
+        assert_eq!(placings[0].scorecard.total, placings[1].scorecard.total);
+        assert_eq!(placings.iter().map(|placing| placing.koi_id).collect::<Vec<_>>(), vec![3, 7]);
+    }
+
+    #[test]
+    fn champion_does_not_take_best_in_variety() {
+        let placings = [
+            placing(1, Variety::Kohaku, 90.0),
+            placing(2, Variety::Kohaku, 80.0),
+            placing(3, Variety::Chagoi, 70.0),
+        ];
+
+        let awarded: Vec<(Award, u32)> = awards(&placings).into_iter().map(|(award, placing)| (award, placing.koi_id)).collect();
+
+        assert_eq!(
+            awarded,
+            vec![
+                (Award::GrandChampion, 1),
+                (Award::BestInVariety(Variety::Kohaku), 2),
+                (Award::BestInVariety(Variety::Chagoi), 3),
+            ]
+        );
+    }
+}
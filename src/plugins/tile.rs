@@ -1,9 +1,11 @@
 use std::collections::HashMap;
 
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_rapier3d::prelude::*;
 
-use super::{hover::Interactable, tools::ToolModeState};
+use serde::{Deserialize, Serialize};
+
+use super::{decoration::Decoration, economy::Wallet, hover::{cast_cursor_ray, Interactable}, notification::Notification, plant::{Plant, PlantSpecies}, pond::{Ponds, NEIGHBOURS}, save::GardenLoaded, season::{CurrentSeason, Season}, tools::ToolModeState};
 
 pub const GRID_SIZE: u32 = 50;
 
@@ -20,7 +22,33 @@ impl Default for TileSettings {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+impl TileSettings {
+    /// Converts a top down world position into the (row, col) coordinates of the tile under it.
+    pub fn grid_coords(&self, position: Vec2) -> IVec2 {
+        ((position + self.grid_offset()) / self.tile_size).round().as_ivec2()
+    }
+
+    /// Converts (row, col) tile coordinates into the top down world position of the tile center.
+    pub fn world_position(&self, coords: IVec2) -> Vec2 {
+        coords.as_vec2() * self.tile_size - self.grid_offset()
+    }
+
+    /// Top down rectangle covered by the whole grid, from the outer edges of the corner tiles.
+    pub fn bounds(&self) -> Rect {
+        let half_tile = Vec2::splat(self.tile_size / 2.0);
+
+        Rect::from_corners(
+            self.world_position(IVec2::ZERO) - half_tile,
+            self.world_position(IVec2::splat(GRID_SIZE as i32 - 1)) + half_tile,
+        )
+    }
+
+    fn grid_offset(&self) -> f32 {
+        (GRID_SIZE as f32 * self.tile_size) / 2.0
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum TileType {
     Grass,
     Dirt,
@@ -28,6 +56,28 @@ pub enum TileType {
     Water,
 }
 
+impl TileType {
+    /// Price of converting a tile into this type, digging out water is the most work.
+    pub fn conversion_cost(&self) -> u32 {
+        match self {
+            TileType::Grass => 5,
+            TileType::Dirt => 10,
+            TileType::Path => 25,
+            TileType::Water => 60,
+        }
+    }
+
+    /// Cost of walking across a tile of this type, water can't be walked across at all.
+    pub fn walk_cost(&self) -> Option<f32> {
+        match self {
+            TileType::Path => Some(1.0),
+            TileType::Grass => Some(2.0),
+            TileType::Dirt => Some(2.5),
+            TileType::Water => None,
+        }
+    }
+}
+
 #[derive(Component, Debug, Clone)]
 pub struct Tile {
     pub tile_type: TileType,
@@ -73,6 +123,14 @@ impl Default for TileGenerator {
 }
 
 impl TileGenerator {
+    /// A generator whose grass takes on the color of `season`.
+    pub fn for_season(season: Season) -> Self {
+        let mut tile_generator = Self::default();
+
+        tile_generator.tile_colors.insert(TileType::Grass, season.grass_color());
+        tile_generator
+    }
+
     pub fn generate(&self, tile_type: TileType, position: &Vec2) -> Tile {
         let color = &self.tile_colors[&tile_type];
         let height = &self.tile_heights[&tile_type];
@@ -93,7 +151,7 @@ impl Plugin for TilePlugin {
         app
             .insert_resource(TileSettings::default())
             .add_systems(Startup, setup)
-            .add_systems(Update, handle_click);
+            .add_systems(Update, (handle_click, load_tiles, recolor_grass));
     }
 }
 
@@ -102,17 +160,13 @@ fn setup(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     tile_settings: Res<TileSettings>,
+    season: Res<CurrentSeason>,
 ) {
-    let tile_generator = TileGenerator::default();
-
-    // test cubes
-    let grid_size = 50;
+    let tile_generator = TileGenerator::for_season(season.0);
 
     for row in 0..GRID_SIZE {
         for col in 0..GRID_SIZE {
-            let x = 0.0 - ((grid_size as f32 * tile_settings.tile_size) / 2.0) + (row as f32 * tile_settings.tile_size);
-            let z = 0.0 - ((grid_size as f32 * tile_settings.tile_size) / 2.0) + (col as f32 * tile_settings.tile_size);
-            let position = Vec2::new(x, z);
+            let position = tile_settings.world_position(IVec2::new(row as i32, col as i32));
             let tile = tile_generator.generate(TileType::Grass, &position);
 
             let material = StandardMaterial {
@@ -143,8 +197,51 @@ fn setup(
     }
 }
 
+/// Things sitting on or relying on a tile that have to be cleared away before the tile can be
+/// repainted, equipment is settled by the equipment plugin instead.
+#[derive(SystemParam)]
+struct TileOccupants<'w, 's> {
+    ponds: Res<'w, Ponds>,
+    decoration_query: Query<'w, 's, &'static Decoration>,
+    plant_query: Query<'w, 's, &'static Plant>,
+}
+
+impl TileOccupants<'_, '_> {
+    /// Names whatever is in the way of repainting the tile at `coords`.
+    fn blocking(&self, coords: IVec2) -> Option<String> {
+        if let Some(decoration) = self.decoration_query.iter().find(|decoration| decoration.covers(coords)) {
+            return Some(decoration.kind.to_string());
+        }
+
+        if self.plant_query.iter().any(|plant| plant.coords == coords) {
+            return Some("plant".to_string());
+        }
+
+        // reeds grow on the bank, filling in the last water beside one would leave it stranded
+        self.plant_query
+            .iter()
+            .any(|plant| plant.species == PlantSpecies::Reed && self.last_water_beside(plant.coords) == Some(coords))
+            .then(|| "reed".to_string())
+    }
+
+    /// The only pond tile next to `coords`, if there is exactly one.
+    fn last_water_beside(&self, coords: IVec2) -> Option<IVec2> {
+        let mut water = NEIGHBOURS
+            .iter()
+            .map(|offset| coords + *offset)
+            .filter(|neighbour| self.ponds.pond_at(*neighbour).is_some());
+
+        match (water.next(), water.next()) {
+            (Some(tile), None) => Some(tile),
+            _ => None,
+        }
+    }
+}
+
 fn handle_click(
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut notifications: EventWriter<Notification>,
+    mut wallet: ResMut<Wallet>,
     rapier_context: Res<RapierContext>,
     mouse_button_input: Res<ButtonInput<MouseButton>>,
     camera_query: Query<(&Camera, &GlobalTransform)>,
@@ -152,29 +249,17 @@ fn handle_click(
     material_query: Query<&Handle<StandardMaterial>, With<Interactable>>,
     mut transform_query: Query<&mut Transform>,
     mut tile_query: Query<&mut Tile, With<Tile>>,
+    occupants: TileOccupants,
     tile_settings: Res<TileSettings>,
+    season: Res<CurrentSeason>,
     state: Res<State<ToolModeState>>,
 ) {
     if *state.get() != ToolModeState::None && mouse_button_input.just_pressed(MouseButton::Left) {
-        let tile_generator = TileGenerator::default();
+        let tile_generator = TileGenerator::for_season(season.0);
 
         let (camera, camera_transform) = camera_query.single();
 
-        let Some(cursor_position) = windows.single().cursor_position() else {
-            return;
-        };
-
-        let Some(ray) = camera.viewport_to_world(camera_transform, cursor_position) else {
-            return;
-        };
-
-        if let Some((entity, _toi)) = rapier_context.cast_ray(
-            ray.origin,
-            ray.direction.into(),
-            f32::MAX,
-            true,
-            QueryFilter::default(),
-        ) {
+        if let Some((entity, _point)) = cast_cursor_ray(camera, camera_transform, windows.single(), &rapier_context) {
             match tile_query.get_mut(entity) {
                 Ok(mut tile) => {
                     let new_tile: Option<Tile> = match *state.get() {
@@ -193,25 +278,26 @@ fn handle_click(
                         _ => None
                     };
 
-                    if let Some(new_tile) = new_tile {
-                        *tile = new_tile.clone();
-
-                        let material_handle = material_query.get(entity).unwrap();
+                    // repainting a tile with its own type is free
+                    let new_tile = new_tile.filter(|new_tile| new_tile.tile_type != tile.tile_type);
 
-                        let material = materials.get_mut(material_handle).unwrap();
+                    if let Some(new_tile) = new_tile {
+                        let coords = tile_settings.grid_coords(to_top_down(tile.position));
 
-                        material.base_color = new_tile.color;
+                        if let Some(occupant) = occupants.blocking(coords) {
+                            notifications.send(Notification(format!("Remove the {} before changing this tile", occupant)));
+                            return;
+                        }
 
-                        if tile.tile_type == TileType::Water {
-                            material.alpha_mode = AlphaMode::Blend;
-                        } else {
-                            material.alpha_mode = AlphaMode::Opaque;
+                        if let Err(error) = wallet.spend(new_tile.tile_type.conversion_cost()) {
+                            notifications.send(Notification(error.to_string()));
+                            return;
                         }
 
+                        let material = materials.get_mut(material_query.get(entity).unwrap()).unwrap();
                         let mut transform = transform_query.get_mut(entity).unwrap();
-                        // using tile_size here because tiles are cubes
-                        transform.scale.y = tile.height / tile_settings.tile_size;
-                        transform.translation = tile.position;
+
+                        replace_tile(&mut *tile, new_tile, material, &mut *transform, &tile_settings);
                     }
                 }
                 Err(_) => {}
@@ -220,6 +306,88 @@ fn handle_click(
     }
 }
 
+/// Replaces `tile` with `new_tile` and updates the tile's material and transform to match.
+fn replace_tile(
+    tile: &mut Tile,
+    new_tile: Tile,
+    material: &mut StandardMaterial,
+    transform: &mut Transform,
+    tile_settings: &TileSettings,
+) {
+    *tile = new_tile;
+
+    material.base_color = tile.color;
+
+    if tile.tile_type == TileType::Water {
+        material.alpha_mode = AlphaMode::Blend;
+    } else {
+        material.alpha_mode = AlphaMode::Opaque;
+    }
+
+    // using tile_size here because tiles are cubes
+    transform.scale.y = tile.height / tile_settings.tile_size;
+    transform.translation = tile.position;
+}
+
+pub fn load_tiles(
+    mut garden_loaded: EventReader<GardenLoaded>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut tile_query: Query<(&mut Tile, &Handle<StandardMaterial>, &mut Transform)>,
+    tile_settings: Res<TileSettings>,
+    season: Res<CurrentSeason>,
+) {
+    let Some(GardenLoaded(save)) = garden_loaded.read().last() else {
+        return;
+    };
+
+    let tile_generator = TileGenerator::for_season(season.0);
+    let saved_types: HashMap<IVec2, TileType> = save
+        .tiles
+        .iter()
+        .map(|record| (IVec2::from(record.coords), record.tile_type))
+        .collect();
+
+    for (mut tile, material_handle, mut transform) in tile_query.iter_mut() {
+        let position = to_top_down(tile.position);
+
+        let Some(tile_type) = saved_types.get(&tile_settings.grid_coords(position)).copied() else {
+            continue;
+        };
+
+        if tile_type == tile.tile_type {
+            continue;
+        }
+
+        let Some(material) = materials.get_mut(material_handle) else {
+            continue;
+        };
+
+        replace_tile(&mut *tile, tile_generator.generate(tile_type, &position), material, &mut *transform, &tile_settings);
+    }
+}
+
+/// Repaints the grass in the color of the new season.
+fn recolor_grass(
+    season: Res<CurrentSeason>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut tile_query: Query<(&mut Tile, &Handle<StandardMaterial>)>,
+) {
+    if !season.is_changed() {
+        return;
+    }
+
+    let color = season.0.grass_color();
+
+    for (mut tile, material_handle) in tile_query.iter_mut().filter(|(tile, _)| tile.tile_type == TileType::Grass) {
+        // only the color changes, the ponds and paths built from the tiles stay as they are
+        tile.bypass_change_detection().color = color;
+
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.base_color = color;
+        }
+    }
+}
+
 pub fn to_top_down(vec_3d: Vec3) -> Vec2 {
     Vec2::new(vec_3d.x, vec_3d.z)
 }
@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use super::camera_controller::CameraMode;
+
 #[derive(Component)]
 pub struct ToolModeStateDisplay;
 
@@ -10,6 +12,17 @@ pub enum ToolModeState {
     Dirt,
     Path,
     Water,
+    Feed,
+    Koi,
+    Pump,
+    Filter,
+    Aerator,
+    Plant,
+    Salt,
+    Medicate,
+    Quarantine,
+    Sell,
+    Decorate,
 }
 
 pub struct ToolsPlugin;
@@ -73,8 +86,14 @@ fn setup(mut commands: Commands) {
 fn handle_input(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     state: Res<State<ToolModeState>>,
-    mut next_state: ResMut<NextState<ToolModeState>>
+    mut next_state: ResMut<NextState<ToolModeState>>,
+    camera_mode: Res<State<CameraMode>>,
 ) {
+    // WASD walks around the garden in first person
+    if *camera_mode.get() == CameraMode::FirstPerson {
+        return;
+    }
+
     if keyboard_input.pressed(KeyCode::KeyG) && *state.get() != ToolModeState::Grass {
         next_state.set(ToolModeState::Grass);
     }
@@ -91,6 +110,50 @@ fn handle_input(
         next_state.set(ToolModeState::Water);
     }
 
+    if keyboard_input.pressed(KeyCode::KeyF) && *state.get() != ToolModeState::Feed {
+        next_state.set(ToolModeState::Feed);
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyK) && *state.get() != ToolModeState::Koi {
+        next_state.set(ToolModeState::Koi);
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyU) && *state.get() != ToolModeState::Pump {
+        next_state.set(ToolModeState::Pump);
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyI) && *state.get() != ToolModeState::Filter {
+        next_state.set(ToolModeState::Filter);
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyO) && *state.get() != ToolModeState::Aerator {
+        next_state.set(ToolModeState::Aerator);
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyL) && *state.get() != ToolModeState::Plant {
+        next_state.set(ToolModeState::Plant);
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyS) && *state.get() != ToolModeState::Salt {
+        next_state.set(ToolModeState::Salt);
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyM) && *state.get() != ToolModeState::Medicate {
+        next_state.set(ToolModeState::Medicate);
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyQ) && *state.get() != ToolModeState::Quarantine {
+        next_state.set(ToolModeState::Quarantine);
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyX) && *state.get() != ToolModeState::Sell {
+        next_state.set(ToolModeState::Sell);
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyE) && *state.get() != ToolModeState::Decorate {
+        next_state.set(ToolModeState::Decorate);
+    }
+
     if keyboard_input.pressed(KeyCode::Escape) && *state.get() != ToolModeState::None {
         next_state.set(ToolModeState::None);
     }
@@ -0,0 +1,318 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use rand::{seq::IteratorRandom, Rng};
+
+use super::{decoration::Decoration, economy::Wallet, koi::Koi, notification::Notification, pathfinding::Pathfinder, plant::Plant, pond::{Ponds, NEIGHBOURS}, save::GardenLoaded, show::judge, tile::{to_top_down, Tile, TileSettings, TileType, GRID_SIZE}};
+
+/// Tile on the edge of the garden visitors arrive at and leave from, it has to be a path for
+/// visitors to come in.
+pub const GATE: IVec2 = IVec2::new(GRID_SIZE as i32 / 2, 0);
+
+// real seconds between visitors arriving
+const ARRIVAL_INTERVAL: f32 = 20.0;
+const MAX_VISITORS: usize = 8;
+const WALK_SPEED: f32 = 2.0;
+const ARRIVAL_DISTANCE: f32 = 0.1;
+// seconds spent admiring each pond
+const VIEWING_TIME: f32 = 3.0;
+// tiles walked before a visitor starts heading back to the gate
+const MAX_WALK: u32 = 60;
+// tiles of path that make for the most pleasant walk
+const IDEAL_WALK: f32 = 30.0;
+const ENTRY_FEE: f32 = 20.0;
+const TIP_PER_STAR: f32 = 5.0;
+// how much each rating moves the garden's reputation
+const REPUTATION_WEIGHT: f32 = 0.1;
+
+/// What a visitor saw on their walk through the garden.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct VisitReport {
+    pub koi_seen: usize,
+    /// Average show score of the koi seen, from 0.0 to 1.0.
+    pub beauty: f32,
+    pub decorations: usize,
+    pub path_length: u32,
+}
+
+impl VisitReport {
+    /// Rating out of 5 stars.
+    pub fn rating(&self) -> f32 {
+        let fish = (self.koi_seen as f32 / 10.0).min(1.0);
+        let decorations = (self.decorations as f32 / 8.0).min(1.0);
+        // short walks are dull and long ones are tiring
+        let walk = (1.0 - (self.path_length as f32 - IDEAL_WALK).abs() / IDEAL_WALK).max(0.0);
+
+        5.0 * (fish * 0.35 + self.beauty * 0.25 + decorations * 0.2 + walk * 0.2)
+    }
+}
+
+/// How well regarded the garden is, from 0.0 to 1.0, built up from the ratings of visitors.
+#[derive(Resource, Debug)]
+pub struct Reputation(pub f32);
+
+#[derive(Component)]
+pub struct Visitor {
+    /// Tiles walked from the gate to the current tile, visitors retrace it to leave.
+    trail: Vec<IVec2>,
+    visited: HashSet<IVec2>,
+    ponds_viewed: HashSet<u32>,
+    koi_seen: HashSet<u32>,
+    beauty: f32,
+    decorations: HashSet<IVec2>,
+    heading_home: bool,
+    pause: Timer,
+}
+
+impl Visitor {
+    fn report(&self) -> VisitReport {
+        VisitReport {
+            koi_seen: self.koi_seen.len(),
+            beauty: if self.koi_seen.is_empty() { 0.0 } else { self.beauty / self.koi_seen.len() as f32 },
+            decorations: self.decorations.len(),
+            path_length: self.visited.len() as u32,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct VisitorAssets {
+    mesh: Handle<Mesh>,
+}
+
+#[derive(Resource)]
+struct VisitorArrivals {
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct ReputationDisplay;
+
+pub struct VisitorPlugin;
+
+impl Plugin for VisitorPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Reputation(0.5))
+            .insert_resource(VisitorArrivals {
+                timer: Timer::from_seconds(ARRIVAL_INTERVAL, TimerMode::Repeating),
+            })
+            .add_systems(Startup, setup)
+            .add_systems(Update, (admit_visitors, walk, display_reputation, load_reputation));
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    tile_settings: Res<TileSettings>,
+) {
+    commands.insert_resource(VisitorAssets {
+        mesh: meshes.add(Capsule3d::new(0.3, 1.0)),
+    });
+
+    // a post either side of the gate
+    let gate = tile_settings.world_position(GATE);
+    let post = meshes.add(Cuboid::new(0.4, 3.0, 0.4));
+    let wood = materials.add(Color::rgb_u8(150, 40, 30));
+
+    for side in [-1.0, 1.0] {
+        commands.spawn(PbrBundle {
+            mesh: post.clone(),
+            material: wood.clone(),
+            transform: Transform::from_xyz(gate.x + side * tile_settings.tile_size * 0.45, 6.5, gate.y),
+            ..default()
+        });
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb_u8(255, 220, 120),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(40.),
+            left: Val::Percent(45.),
+            ..default()
+        }),
+        ReputationDisplay,
+    ));
+}
+
+fn admit_visitors(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+    mut arrivals: ResMut<VisitorArrivals>,
+    mut wallet: ResMut<Wallet>,
+    reputation: Res<Reputation>,
+    visitor_assets: Res<VisitorAssets>,
+    tile_settings: Res<TileSettings>,
+    tile_query: Query<&Tile>,
+    visitor_query: Query<(), With<Visitor>>,
+) {
+    if !arrivals.timer.tick(time.delta()).just_finished() || visitor_query.iter().count() >= MAX_VISITORS {
+        return;
+    }
+
+    let Some(gate) = tile_query
+        .iter()
+        .find(|tile| tile.tile_type == TileType::Path && tile_settings.grid_coords(to_top_down(tile.position)) == GATE) else {
+        return;
+    };
+
+    let mut rng = rand::thread_rng();
+    let gate_position = tile_settings.world_position(GATE);
+
+    // a better reputation lets the garden charge more at the gate
+    wallet.earn((ENTRY_FEE * (0.5 + reputation.0)).round() as u32);
+
+    commands.spawn((
+        PbrBundle {
+            mesh: visitor_assets.mesh.clone(),
+            material: materials.add(Color::hsl(rng.gen_range(0.0..360.0), 0.5, 0.5)),
+            transform: Transform::from_xyz(gate_position.x, gate.height + 0.8, gate_position.y),
+            ..default()
+        },
+        Visitor {
+            trail: vec![GATE],
+            visited: HashSet::from([GATE]),
+            ponds_viewed: HashSet::new(),
+            koi_seen: HashSet::new(),
+            beauty: 0.0,
+            decorations: HashSet::new(),
+            heading_home: false,
+            pause: Timer::from_seconds(0.0, TimerMode::Once),
+        },
+    ));
+}
+
+/// Visitors explore the path network one tile at a time, stopping to look at every pond the path
+/// passes, and retrace their steps to the gate once they have seen it all. Visitors who tire of
+/// walking take the shortest route back instead.
+fn walk(
+    mut commands: Commands,
+    mut notifications: EventWriter<Notification>,
+    time: Res<Time>,
+    mut wallet: ResMut<Wallet>,
+    mut reputation: ResMut<Reputation>,
+    pathfinder: Res<Pathfinder>,
+    ponds: Res<Ponds>,
+    tile_settings: Res<TileSettings>,
+    koi_query: Query<(&Koi, &Transform), Without<Visitor>>,
+    plant_query: Query<&Plant>,
+    decoration_query: Query<&Decoration>,
+    mut visitor_query: Query<(Entity, &mut Visitor, &mut Transform)>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for (entity, mut visitor, mut transform) in visitor_query.iter_mut() {
+        if !visitor.pause.tick(time.delta()).finished() {
+            continue;
+        }
+
+        let Some(current) = visitor.trail.last().copied() else {
+            continue;
+        };
+
+        // paths are all the same height so visitors only ever walk across
+        let position = tile_settings.world_position(current);
+        let destination = Vec3::new(position.x, transform.translation.y, position.y);
+        let offset = destination - transform.translation;
+
+        if offset.length() > ARRIVAL_DISTANCE {
+            transform.translation += offset.clamp_length_max(WALK_SPEED * time.delta_seconds());
+            continue;
+        }
+
+        // stop at ponds next to the path
+        if let Some(pond) = ponds.pond_touching(current).filter(|pond| !visitor.ponds_viewed.contains(&pond.id)) {
+            visitor.ponds_viewed.insert(pond.id);
+
+            for (koi, koi_transform) in koi_query.iter() {
+                let in_pond = pond.tiles.contains(&tile_settings.grid_coords(to_top_down(koi_transform.translation)));
+
+                if in_pond && visitor.koi_seen.insert(koi.id) {
+                    visitor.beauty += judge(koi).total / 100.0;
+                }
+            }
+
+            visitor.pause = Timer::from_seconds(VIEWING_TIME, TimerMode::Once);
+        }
+
+        for plant in plant_query.iter() {
+            if (plant.coords - current).abs().max_element() <= 1 {
+                visitor.decorations.insert(plant.coords);
+            }
+        }
+
+        for decoration in decoration_query.iter() {
+            if decoration.tiles.iter().any(|coords| (*coords - current).abs().max_element() <= 1) {
+                visitor.decorations.insert(decoration.coords);
+            }
+        }
+
+        let next = NEIGHBOURS
+            .iter()
+            .map(|offset| current + *offset)
+            .filter(|coords| pathfinder.tile_type(*coords) == Some(TileType::Path) && !visitor.visited.contains(coords))
+            .choose(&mut rng)
+            .filter(|_| !visitor.heading_home);
+
+        match next {
+            Some(next) => {
+                visitor.trail.push(next);
+                visitor.visited.insert(next);
+                visitor.heading_home = visitor.visited.len() as u32 >= MAX_WALK;
+
+                // tired visitors take the shortest way back rather than retracing every step
+                if visitor.heading_home {
+                    if let Some(route) = pathfinder.find_path_on(GATE, next, |tile_type| tile_type == TileType::Path) {
+                        visitor.trail = route;
+                    }
+                }
+            }
+            None => {
+                visitor.trail.pop();
+            }
+        }
+
+        if !visitor.trail.is_empty() {
+            continue;
+        }
+
+        let rating = visitor.report().rating();
+
+        reputation.0 += (rating / 5.0 - reputation.0) * REPUTATION_WEIGHT;
+        wallet.earn((rating * TIP_PER_STAR).round() as u32);
+        notifications.send(Notification(format!("A visitor rated the garden {:.1} stars", rating)));
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn display_reputation(
+    reputation: Res<Reputation>,
+    visitor_query: Query<(), With<Visitor>>,
+    mut text_query: Query<&mut Text, With<ReputationDisplay>>,
+) {
+    let mut text = text_query.single_mut();
+
+    text.sections[0].value = format!(
+        "Reputation {:.1} stars | {} visitors",
+        reputation.0 * 5.0,
+        visitor_query.iter().count(),
+    );
+}
+
+fn load_reputation(mut garden_loaded: EventReader<GardenLoaded>, mut reputation: ResMut<Reputation>) {
+    // saves from before the reputation keep the current one
+    if let Some(saved) = garden_loaded.read().last().and_then(|GardenLoaded(save)| save.reputation) {
+        reputation.0 = saved;
+    }
+}
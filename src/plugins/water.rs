@@ -1,7 +1,14 @@
 use bevy::{pbr::NotShadowCaster, prelude::*};
 use bevy_water::{material::{StandardWaterMaterial, WaterMaterial}, WaterPlugin as BevyWaterPlugin, *};
 
-use super::tile::{TileSettings, GRID_SIZE};
+use super::{pond::Ponds, tile::{TileSettings, GRID_SIZE}, weather::Weather};
+
+/// Height of the water surface in world space.
+pub const WATER_LEVEL: f32 = 4.0;
+
+// colors the water fades towards as its clarity drops
+const MURKY_DEEP_COLOR: Color = Color::rgb(0.18, 0.2, 0.09);
+const MURKY_SHALLOW_COLOR: Color = Color::rgb(0.36, 0.38, 0.2);
 
 pub struct WaterPlugin;
 
@@ -12,7 +19,8 @@ impl Plugin for WaterPlugin {
                 ..default()
             })
             .add_plugins(BevyWaterPlugin)
-            .add_systems(Startup, setup);
+            .add_systems(Startup, setup)
+            .add_systems(Update, (tint_water, raise_water, stir_waves));
     }
 }
 
@@ -46,9 +54,81 @@ fn setup(
         MaterialMeshBundle {
             mesh,
             material,
-            transform: Transform::from_xyz(-tile_settings.tile_size / 2.0, 4.0, -tile_settings.tile_size / 2.0),
+            transform: Transform::from_xyz(-tile_settings.tile_size / 2.0, WATER_LEVEL, -tile_settings.tile_size / 2.0),
             ..default()
         },
         NotShadowCaster
     ));
 }
+
+fn tint_water(
+    ponds: Res<Ponds>,
+    water_settings: Res<WaterSettings>,
+    mut materials: ResMut<Assets<StandardWaterMaterial>>,
+    material_query: Query<&Handle<StandardWaterMaterial>>,
+) {
+    if !ponds.is_changed() {
+        return;
+    }
+
+    // there is a single water surface so it takes on the average clarity of all ponds
+    let (clarity_sum, tile_count) = ponds.iter().fold((0.0, 0), |(clarity_sum, tile_count), pond| {
+        (clarity_sum + pond.chemistry.clarity * pond.tiles.len() as f32, tile_count + pond.tiles.len())
+    });
+
+    let murkiness = if tile_count == 0 { 0.0 } else { 1.0 - clarity_sum / tile_count as f32 };
+
+    for material_handle in material_query.iter() {
+        let Some(material) = materials.get_mut(material_handle) else {
+            continue;
+        };
+
+        material.extension.deep_color = mix_colors(water_settings.deep_color, MURKY_DEEP_COLOR, murkiness);
+        material.extension.shallow_color = mix_colors(water_settings.shallow_color, MURKY_SHALLOW_COLOR, murkiness);
+        material.extension.clarity = water_settings.clarity * (1.0 - murkiness);
+    }
+}
+
+/// Lifts the water surface as rain fills the ponds. Like the color, the single surface follows
+/// the average over all ponds.
+fn raise_water(ponds: Res<Ponds>, mut water_query: Query<&mut Transform, With<Handle<StandardWaterMaterial>>>) {
+    if !ponds.is_changed() {
+        return;
+    }
+
+    let (level_sum, tile_count) = ponds.iter().fold((0.0, 0), |(level_sum, tile_count), pond| {
+        (level_sum + pond.level * pond.tiles.len() as f32, tile_count + pond.tiles.len())
+    });
+
+    let level = if tile_count == 0 { 0.0 } else { level_sum / tile_count as f32 };
+
+    for mut transform in water_query.iter_mut() {
+        transform.translation.y = WATER_LEVEL + level;
+    }
+}
+
+/// Wind whips up waves taller than the calm ones set in the water settings.
+fn stir_waves(
+    weather: Res<Weather>,
+    water_settings: Res<WaterSettings>,
+    mut materials: ResMut<Assets<StandardWaterMaterial>>,
+    material_query: Query<&Handle<StandardWaterMaterial>>,
+) {
+    if !weather.is_changed() {
+        return;
+    }
+
+    for material_handle in material_query.iter() {
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.extension.amplitude = water_settings.amplitude * weather.wave_scale();
+        }
+    }
+}
+
+/// Blends from one color to another, `amount` 0.0 is all `from` and 1.0 is all `to`.
+pub fn mix_colors(from: Color, to: Color, amount: f32) -> Color {
+    let from = Vec4::from(from.as_rgba_f32());
+    let to = Vec4::from(to.as_rgba_f32());
+
+    Color::rgba_from_array(from.lerp(to, amount))
+}
@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+
+// rates are per simulated second
+const REAERATION_RATE: f32 = 0.01;
+const AERATOR_OXYGEN: f32 = 0.05;
+// moving water picks up a little oxygen at the surface
+const FLOW_OXYGEN: f32 = 0.01;
+const PLANT_OXYGEN: f32 = 0.02;
+const FISH_OXYGEN_DEMAND: f32 = 0.002;
+const FISH_AMMONIA: f32 = 0.0005;
+// ammonia produced per unit of rotting food in a cubic metre of water
+const WASTE_AMMONIA: f32 = 0.5;
+const NITRIFICATION_RATE: f32 = 0.005;
+const NITRIFICATION_OXYGEN: f32 = 4.0;
+const FILTER_RATE: f32 = 0.02;
+const PLANT_NITRATE_UPTAKE: f32 = 0.01;
+const TEMPERATURE_RATE: f32 = 0.002;
+const SHADE_COOLING: f32 = 4.0;
+const CLARITY_RATE: f32 = 0.01;
+
+/// Chemistry of a single pond.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WaterChemistry {
+    /// Dissolved oxygen in mg/L.
+    pub oxygen: f32,
+    /// Ammonia in ppm.
+    pub ammonia: f32,
+    /// Nitrate in ppm.
+    pub nitrate: f32,
+    /// Temperature in degrees Celsius.
+    pub temperature: f32,
+    /// How clear the water is, from 0.0 (opaque) to 1.0 (crystal clear).
+    pub clarity: f32,
+}
+
+impl Default for WaterChemistry {
+    fn default() -> Self {
+        Self {
+            oxygen: 9.0,
+            ammonia: 0.0,
+            nitrate: 5.0,
+            temperature: 20.0,
+            clarity: 1.0,
+        }
+    }
+}
+
+/// Everything outside the water itself that influences a pond's chemistry during a tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PondConditions {
+    /// Volume of the pond in cubic metres.
+    pub volume: f32,
+    pub fish_count: u32,
+    /// Rotting food that entered the pond during the tick.
+    pub waste: f32,
+    /// Fraction of the pond surface covered by plants, from 0.0 to 1.0.
+    pub plant_cover: f32,
+    /// Rated filter capacity relative to the pond volume, 1.0 covers the whole pond.
+    pub filtration: f32,
+    /// Rated aerator capacity relative to the pond volume, 1.0 covers the whole pond.
+    pub aeration: f32,
+    /// Rated pump capacity relative to the pond volume, 1.0 covers the whole pond.
+    pub flow: f32,
+    pub air_temperature: f32,
+}
+
+impl Default for PondConditions {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            fish_count: 0,
+            waste: 0.0,
+            plant_cover: 0.0,
+            filtration: 0.0,
+            aeration: 0.0,
+            flow: 0.0,
+            air_temperature: 20.0,
+        }
+    }
+}
+
+impl WaterChemistry {
+    /// Oxygen the water can hold at its current temperature, in mg/L.
+    pub fn oxygen_saturation(&self) -> f32 {
+        let t = self.temperature;
+
+        (14.6 - 0.39 * t + 0.007 * t * t).max(0.0)
+    }
+
+    /// Advances the chemistry by `delta` simulated seconds.
+    pub fn step(&mut self, conditions: &PondConditions, delta: f32) {
+        let volume = conditions.volume.max(f32::EPSILON);
+        let fish_density = conditions.fish_count as f32 / volume;
+
+        let nitrified = self.ammonia * NITRIFICATION_RATE * delta;
+        let filtered = self.ammonia * (conditions.filtration * FILTER_RATE * delta).min(1.0);
+
+        self.ammonia += fish_density * FISH_AMMONIA * delta + conditions.waste * WASTE_AMMONIA / volume;
+        self.ammonia = (self.ammonia - nitrified - filtered).max(0.0);
+
+        self.nitrate += nitrified;
+        self.nitrate -= self.nitrate * (conditions.plant_cover * PLANT_NITRATE_UPTAKE * delta).min(1.0);
+        self.nitrate = self.nitrate.max(0.0);
+
+        let saturation = self.oxygen_saturation();
+        let reaeration = REAERATION_RATE + conditions.aeration * AERATOR_OXYGEN + conditions.flow * FLOW_OXYGEN;
+
+        self.oxygen += (saturation - self.oxygen) * (reaeration * delta).min(1.0);
+        self.oxygen += conditions.plant_cover * PLANT_OXYGEN * delta;
+        self.oxygen -= fish_density * FISH_OXYGEN_DEMAND * delta + nitrified * NITRIFICATION_OXYGEN;
+        self.oxygen = self.oxygen.max(0.0);
+
+        // plants shade the water and keep it cooler than the air
+        let target_temperature = conditions.air_temperature - conditions.plant_cover * SHADE_COOLING;
+        self.temperature += (target_temperature - self.temperature) * (TEMPERATURE_RATE * delta).min(1.0);
+
+        self.clarity += (self.target_clarity(conditions) - self.clarity) * (CLARITY_RATE * delta).min(1.0);
+        self.clarity = self.clarity.clamp(0.0, 1.0);
+    }
+
+    /// Mixes in fresh water making up `fraction` of the pond, which carries no ammonia or nitrate.
+    pub fn dilute(&mut self, fraction: f32) {
+        let remaining = 1.0 - fraction.clamp(0.0, 1.0);
+
+        self.ammonia *= remaining;
+        self.nitrate *= remaining;
+    }
+
+    // nitrate feeds algae and ammonia comes with suspended waste, filters, circulation and plants
+    // fight both
+    fn target_clarity(&self, conditions: &PondConditions) -> f32 {
+        let murk = self.nitrate / 100.0 + self.ammonia / 2.0;
+        let cleaning = 1.0 + conditions.filtration * 10.0 + conditions.flow * 2.0 + conditions.plant_cover;
+
+        (1.0 - murk / cleaning).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // one simulation tick
+    const DELTA: f32 = 60.0;
+
+    fn stepped(mut chemistry: WaterChemistry, conditions: PondConditions) -> WaterChemistry {
+        chemistry.step(&conditions, DELTA);
+        chemistry
+    }
+
+    #[test]
+    fn fish_raise_ammonia() {
+        let empty = stepped(WaterChemistry::default(), PondConditions::default());
+        let stocked = stepped(WaterChemistry::default(), PondConditions { fish_count: 20, ..Default::default() });
+
+        assert!(stocked.ammonia > empty.ammonia);
+    }
+
+    #[test]
+    fn waste_raises_ammonia() {
+        let clean = stepped(WaterChemistry::default(), PondConditions::default());
+        let dirty = stepped(WaterChemistry::default(), PondConditions { waste: 1.0, ..Default::default() });
+
+        assert!(dirty.ammonia > clean.ammonia);
+    }
+
+    #[test]
+    fn filters_lower_ammonia() {
+        let polluted = WaterChemistry { ammonia: 1.0, ..Default::default() };
+
+        let unfiltered = stepped(polluted, PondConditions::default());
+        let filtered = stepped(polluted, PondConditions { filtration: 1.0, ..Default::default() });
+
+        assert!(filtered.ammonia < unfiltered.ammonia);
+        assert!(filtered.ammonia < polluted.ammonia);
+    }
+
+    #[test]
+    fn aeration_restores_oxygen() {
+        let starved = WaterChemistry { oxygen: 2.0, ..Default::default() };
+
+        let still = stepped(starved, PondConditions::default());
+        let aerated = stepped(starved, PondConditions { aeration: 1.0, ..Default::default() });
+
+        assert!(aerated.oxygen > still.oxygen);
+        assert!(aerated.oxygen > starved.oxygen);
+        assert!(aerated.oxygen <= starved.oxygen_saturation());
+    }
+
+    #[test]
+    fn nitrate_clouds_the_water() {
+        let mut clean = WaterChemistry::default();
+        let mut green = WaterChemistry { nitrate: 80.0, ..Default::default() };
+
+        for _ in 0..10 {
+            clean.step(&PondConditions::default(), DELTA);
+            green.step(&PondConditions::default(), DELTA);
+        }
+
+        assert!(green.clarity < clean.clarity);
+    }
+
+    #[test]
+    fn temperature_follows_the_air() {
+        let start = WaterChemistry { temperature: 20.0, ..Default::default() };
+
+        let warmed = stepped(start, PondConditions { air_temperature: 30.0, ..Default::default() });
+        let cooled = stepped(start, PondConditions { air_temperature: 5.0, ..Default::default() });
+
+        assert!(warmed.temperature > 20.0 && warmed.temperature <= 30.0);
+        assert!(cooled.temperature < 20.0 && cooled.temperature >= 5.0);
+    }
+}
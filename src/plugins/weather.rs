@@ -0,0 +1,365 @@
+use std::fmt;
+
+use bevy::{pbr::{FogFalloff, FogSettings}, prelude::*};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::{light::TimeOfDay, notification::Notification, save::{GardenLoaded, WeatherRecord}, water::mix_colors};
+
+// real seconds at normal speed each spell of weather lasts
+const MIN_SPELL: f32 = 90.0;
+const MAX_SPELL: f32 = 300.0;
+// real seconds one kind of weather takes to blend into the next
+const TRANSITION_TIME: f32 = 20.0;
+const DAY_FOG_COLOR: Color = Color::rgb(0.75, 0.78, 0.8);
+const NIGHT_FOG_COLOR: Color = Color::rgb(0.08, 0.09, 0.12);
+// fix the weather seed so a run of weather can be watched again, the flag wins over the variable
+const SEED_FLAG: &str = "--weather-seed";
+const SEED_VAR: &str = "KOI_WEATHER_SEED";
+// spreads the change count over the seed so neighbouring seeds don't replay each other's weather
+const CHANGE_MIX: u64 = 0x9E37_79B9_7F4A_7C15;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum WeatherKind {
+    Clear,
+    Overcast,
+    Rain,
+    Wind,
+    Fog,
+}
+
+impl WeatherKind {
+    const ALL: [WeatherKind; 5] = [WeatherKind::Clear, WeatherKind::Overcast, WeatherKind::Rain, WeatherKind::Wind, WeatherKind::Fog];
+
+    /// Relative chance of `next` following this weather, clouds come before rain and fog lifts
+    /// into a clear sky.
+    fn chance_of(&self, next: WeatherKind) -> u32 {
+        use WeatherKind::*;
+
+        match (self, next) {
+            (Clear, Clear) => 4,
+            (Clear, Overcast) => 3,
+            (Clear, Wind) => 2,
+            (Clear, Fog) => 1,
+            (Overcast, Clear) => 2,
+            (Overcast, Overcast) => 1,
+            (Overcast, Rain) => 4,
+            (Overcast, Wind) => 1,
+            (Overcast, Fog) => 1,
+            (Rain, Overcast) => 3,
+            (Rain, Rain) => 1,
+            (Rain, Wind) => 2,
+            (Wind, Clear) => 3,
+            (Wind, Overcast) => 2,
+            (Wind, Rain) => 1,
+            (Fog, Clear) => 3,
+            (Fog, Overcast) => 1,
+            (Fog, Fog) => 1,
+            _ => 0,
+        }
+    }
+
+    /// Metres of rain falling per simulated second.
+    fn rainfall(&self) -> f32 {
+        match self {
+            WeatherKind::Rain => 0.0005,
+            _ => 0.0,
+        }
+    }
+
+    /// Fraction of the sunlight that makes it through the clouds.
+    fn sunlight(&self) -> f32 {
+        match self {
+            WeatherKind::Clear | WeatherKind::Wind => 1.0,
+            WeatherKind::Overcast => 0.5,
+            WeatherKind::Rain => 0.3,
+            WeatherKind::Fog => 0.6,
+        }
+    }
+
+    /// How much taller the waves are than on a calm day.
+    fn wave_scale(&self) -> f32 {
+        match self {
+            WeatherKind::Wind => 4.0,
+            WeatherKind::Rain => 1.5,
+            _ => 1.0,
+        }
+    }
+
+    /// Density of the distance fog, rain leaves a light haze.
+    fn fog_density(&self) -> f32 {
+        match self {
+            WeatherKind::Fog => 0.04,
+            WeatherKind::Rain => 0.012,
+            _ => 0.0,
+        }
+    }
+
+    fn announcement(&self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "The sky clears",
+            WeatherKind::Overcast => "Clouds roll in over the garden",
+            WeatherKind::Rain => "It starts to rain",
+            WeatherKind::Wind => "The wind picks up",
+            WeatherKind::Fog => "Fog settles over the garden",
+        }
+    }
+}
+
+impl fmt::Display for WeatherKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeatherKind::Clear => write!(f, "Clear"),
+            WeatherKind::Overcast => write!(f, "Overcast"),
+            WeatherKind::Rain => write!(f, "Rain"),
+            WeatherKind::Wind => write!(f, "Wind"),
+            WeatherKind::Fog => write!(f, "Fog"),
+        }
+    }
+}
+
+/// The weather over the garden. Each change is drawn from the weather seed so the same garden
+/// always sees the same weather in the same order.
+#[derive(Resource, Debug)]
+pub struct Weather {
+    pub seed: u64,
+    /// Number of times the weather has changed.
+    pub changes: u64,
+    pub kind: WeatherKind,
+    /// Real seconds at normal speed the current weather lasts.
+    pub spell: f32,
+    previous: WeatherKind,
+    // how far the previous weather has blended into the current one, from 0.0 to 1.0
+    blend: f32,
+}
+
+impl Weather {
+    /// Every garden starts out under a clear sky.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            changes: 0,
+            kind: WeatherKind::Clear,
+            spell: MIN_SPELL,
+            previous: WeatherKind::Clear,
+            blend: 1.0,
+        }
+    }
+
+    /// The weather of a garden with `seed` after it has changed `changes` times.
+    pub fn replay(seed: u64, changes: u64) -> Self {
+        let mut weather = Self::new(seed);
+
+        for _ in 0..changes {
+            weather.change();
+        }
+
+        weather.blend = 1.0;
+        weather
+    }
+
+    /// Moves on to the next spell of weather.
+    pub fn change(&mut self) {
+        let mut rng = StdRng::seed_from_u64(self.seed ^ self.changes.wrapping_mul(CHANGE_MIX));
+        let current = self.kind;
+        let next = WeatherKind::ALL.choose_weighted(&mut rng, |next| current.chance_of(*next)).copied().unwrap_or(WeatherKind::Clear);
+
+        self.previous = current;
+        self.kind = next;
+        self.spell = rng.gen_range(MIN_SPELL..MAX_SPELL);
+        self.blend = 0.0;
+        self.changes += 1;
+    }
+
+    fn blended(&self, value: impl Fn(&WeatherKind) -> f32) -> f32 {
+        value(&self.previous) + (value(&self.kind) - value(&self.previous)) * self.blend
+    }
+
+    /// Metres of rain falling on the ponds per simulated second.
+    pub fn rainfall(&self) -> f32 {
+        self.blended(WeatherKind::rainfall)
+    }
+
+    /// Fraction of the sunlight that makes it through the clouds.
+    pub fn sunlight(&self) -> f32 {
+        self.blended(WeatherKind::sunlight)
+    }
+
+    /// How much taller the waves are than on a calm day.
+    pub fn wave_scale(&self) -> f32 {
+        self.blended(WeatherKind::wave_scale)
+    }
+
+    pub fn fog_density(&self) -> f32 {
+        self.blended(WeatherKind::fog_density)
+    }
+}
+
+/// Real seconds the current weather has lasted, kept apart from the weather itself so the
+/// weather only shows as changed when it really does.
+#[derive(Resource, Default)]
+struct WeatherClock {
+    elapsed: f32,
+}
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Weather::new(rand::random()))
+            .insert_resource(WeatherClock::default())
+            .add_systems(Startup, seed_weather)
+            .add_systems(Update, (advance_weather, load_weather, update_fog).chain());
+    }
+}
+
+fn configured_seed() -> Option<String> {
+    std::env::args()
+        .skip_while(|arg| arg != SEED_FLAG)
+        .nth(1)
+        .or_else(|| std::env::var(SEED_VAR).ok())
+}
+
+/// Swaps the random weather seed for the one given on the command line or in the environment.
+fn seed_weather(mut notifications: EventWriter<Notification>, mut weather: ResMut<Weather>) {
+    let Some(value) = configured_seed() else {
+        return;
+    };
+
+    match value.parse::<u64>() {
+        Ok(seed) => *weather = Weather::new(seed),
+        Err(_) => {
+            notifications.send(Notification(format!("Weather seed {} is not a number, the weather is random", value)));
+        }
+    }
+}
+
+/// Weather follows the garden clock, it changes faster when days go by faster and holds still
+/// while time is paused.
+fn advance_weather(
+    time: Res<Time>,
+    time_of_day: Res<TimeOfDay>,
+    mut notifications: EventWriter<Notification>,
+    mut clock: ResMut<WeatherClock>,
+    mut weather: ResMut<Weather>,
+) {
+    if time_of_day.paused {
+        return;
+    }
+
+    let delta = time.delta_seconds() * time_of_day.speed;
+
+    if weather.blend < 1.0 {
+        weather.blend = (weather.blend + delta / TRANSITION_TIME).min(1.0);
+    }
+
+    clock.elapsed += delta;
+
+    if clock.elapsed < weather.spell {
+        return;
+    }
+
+    clock.elapsed = 0.0;
+    weather.change();
+
+    if weather.kind != weather.previous {
+        notifications.send(Notification(weather.kind.announcement().into()));
+    }
+}
+
+fn load_weather(mut garden_loaded: EventReader<GardenLoaded>, mut clock: ResMut<WeatherClock>, mut weather: ResMut<Weather>) {
+    let Some(GardenLoaded(save)) = garden_loaded.read().last() else {
+        return;
+    };
+
+    // saves from before the weather keep the current weather
+    if let Some(WeatherRecord { seed, changes }) = save.weather {
+        *weather = Weather::replay(seed, changes);
+        clock.elapsed = 0.0;
+    }
+}
+
+/// Thickens and thins the distance fog with the weather, fading it into the dark at night.
+fn update_fog(
+    mut commands: Commands,
+    weather: Res<Weather>,
+    time_of_day: Res<TimeOfDay>,
+    camera_query: Query<Entity, (With<Camera3d>, Without<FogSettings>)>,
+    mut fog_query: Query<&mut FogSettings>,
+) {
+    let color = mix_colors(NIGHT_FOG_COLOR, DAY_FOG_COLOR, time_of_day.daylight());
+    let falloff = FogFalloff::ExponentialSquared { density: weather.fog_density() };
+
+    for entity in camera_query.iter() {
+        commands.entity(entity).insert(FogSettings {
+            color,
+            falloff: falloff.clone(),
+            ..default()
+        });
+    }
+
+    if !weather.is_changed() && !time_of_day.is_changed() {
+        return;
+    }
+
+    for mut fog in fog_query.iter_mut() {
+        fog.color = color;
+        fog.falloff = falloff.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence(seed: u64, changes: u64) -> Vec<(WeatherKind, f32)> {
+        let mut weather = Weather::new(seed);
+
+        (0..changes)
+            .map(|_| {
+                weather.change();
+                (weather.kind, weather.spell)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn replay_matches_changing_step_by_step() {
+        let mut weather = Weather::new(42);
+
+        for changes in 0..50 {
+            let replayed = Weather::replay(42, changes);
+
+            assert_eq!(replayed.changes, weather.changes);
+            assert_eq!(replayed.kind, weather.kind);
+            assert_eq!(replayed.spell, weather.spell);
+            assert_eq!(replayed.previous, weather.previous);
+
+            weather.change();
+        }
+    }
+
+    #[test]
+    fn same_seed_same_weather() {
+        assert_eq!(sequence(7, 100), sequence(7, 100));
+        assert_ne!(sequence(7, 100), sequence(8, 100));
+    }
+
+    #[test]
+    fn neighbouring_seeds_are_not_shifted() {
+        let seven = sequence(7, 100);
+        let eight = sequence(8, 100);
+
+        // once two shifted runs reach the same weather they would match from then on
+        assert!(seven[1..].iter().zip(eight.iter()).all(|(seven, eight)| seven != eight));
+        assert!(eight[1..].iter().zip(seven.iter()).all(|(eight, seven)| eight != seven));
+    }
+
+    #[test]
+    fn weather_stays_within_its_spells() {
+        for (_, spell) in sequence(3, 100) {
+            assert!((MIN_SPELL..MAX_SPELL).contains(&spell));
+        }
+    }
+}